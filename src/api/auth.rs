@@ -0,0 +1,70 @@
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+
+use crate::domain::error::LedgerError;
+
+/// Bearer tokens of the operators allowed to make and check requests, by token.
+///
+/// Read from the `OPERATOR_TOKENS` environment variable as comma-separated
+/// `operator:token` pairs, e.g. `ada:s3cret,musa:0th3r`. With none configured
+/// no operator can authenticate, so every operator-only endpoint is refused.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorTokens {
+    operators: HashMap<String, String>,
+}
+
+impl OperatorTokens {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut operators = HashMap::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (operator, token) = pair
+                .split_once(':')
+                .map(|(o, t)| (o.trim(), t.trim()))
+                .filter(|(o, t)| !o.is_empty() && !t.is_empty())
+                .ok_or_else(|| format!("Expected operator:token, got '{}'", pair))?;
+            if operators.insert(token.to_string(), operator.to_string()).is_some() {
+                return Err(format!("Operator {} shares its token with another operator", operator));
+            }
+        }
+        Ok(OperatorTokens { operators })
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        std::env::var("OPERATOR_TOKENS").map_or(Ok(OperatorTokens::default()), |spec| Self::parse(&spec))
+    }
+
+    /// The operator authenticated by the request's `Authorization: Bearer`
+    /// token, if it sent one. An `X-Operator-Id` header is only accepted
+    /// alongside the token and must name the same operator.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<String>, LedgerError> {
+        let claimed = claimed_operator(headers)?;
+        let Some(value) = headers.get("authorization") else {
+            return match claimed {
+                Some(_) => Err(LedgerError::Unauthorized("X-Operator-Id requires a bearer token".into())),
+                None => Ok(None),
+            };
+        };
+        let operator = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .and_then(|token| self.operators.get(token.trim()))
+            .ok_or_else(|| LedgerError::Unauthorized("Operator token is not recognised".into()))?;
+        if claimed.is_some_and(|claimed| claimed != *operator) {
+            return Err(LedgerError::Unauthorized("X-Operator-Id does not match the operator token".into()));
+        }
+        Ok(Some(operator.clone()))
+    }
+}
+
+/// The `X-Operator-Id` header when the caller sent one.
+fn claimed_operator(headers: &HeaderMap) -> Result<Option<String>, LedgerError> {
+    let Some(value) = headers.get("x-operator-id") else {
+        return Ok(None);
+    };
+    let id = value
+        .to_str()
+        .map_err(|_| LedgerError::validation("X-Operator-Id must be visible ASCII"))?
+        .trim();
+    Ok((!id.is_empty()).then(|| id.to_string()))
+}
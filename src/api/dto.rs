@@ -1,20 +1,28 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::currency::Currency;
-
-/// Kobo type alias (₦1 = 100 Kobo)
-pub type Kobo = i64;
+use crate::domain::{account::{Account, AccountType, FreezeScope, SystemRole}, approval::ApprovalStatus, currency::Currency, customer::{Customer, KycStatus}, fee::{FeeOperation, FeeSchedule}, fx::FxConversion, interest::InterestTerms, kyc::KycTier, levy::LevyKind, lien::Lien, mandate::{InstructionStatus, MandateRule}, money::{Amount, Money}, standing_order::{FailurePolicy, Frequency}, transaction::TransactionEntry};
 
 /// --- Account DTOs ---
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAccountRequest {
     pub owner: String,
-    pub initial: Kobo,
+    /// Minor units (`10000`) or a decimal string (`"100.00"`) in `currency`.
+    pub initial: Amount,
     pub currency: Currency,
      #[serde(rename = "bankName")]
     pub bank_name: String,
     #[serde(rename = "bankCode")]
     pub bank_code: String,
+    /// Group account to file the new account under.
+    #[serde(rename = "parentId", default)]
+    pub parent_id: Option<u32>,
+    /// Open the account as pending until it is activated.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+    /// Customer to attach the new account to.
+    #[serde(rename = "customerId", default, skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<u64>,
 }
 
 #[derive(Debug, Serialize,Deserialize)]
@@ -29,29 +37,416 @@ pub struct CreateAccountResponse {
     pub currency: Currency,
 }
 
-/// --- Deposit / Withdraw DTO ---
 #[derive(Debug, Deserialize)]
+pub struct CreateGroupAccountRequest {
+    pub name: String,
+    #[serde(rename = "accountType")]
+    pub account_type: AccountType,
+    /// Nominal currency of the group; roll-ups report every currency below it.
+    #[serde(default = "default_group_currency")]
+    pub currency: Currency,
+    #[serde(rename = "parentId", default)]
+    pub parent_id: Option<u32>,
+}
+
+fn default_group_currency() -> Currency {
+    Currency::NGN
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateGroupAccountResponse {
+    pub id: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReverseRequest {
+    pub reason: String,
+    /// Customer giving the instruction when the reversal debits a joint account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OverdraftRequest {
+    /// Minor units or a decimal string, in the account's currency.
+    pub limit: Amount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetParentRequest {
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceResponse {
+    /// Balance in minor units.
+    pub balance: i64,
+    pub currency: Currency,
+    /// Balance formatted for display, e.g. `₦1250.50`.
+    pub formatted: String,
+    /// Balance less active authorization holds and liens, in minor units.
+    /// Only reported for the current balance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_balance: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_formatted: Option<String>,
+    /// Point in time the balance was computed for, when not the present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+impl BalanceResponse {
+    pub fn new(balance: Money, available: Money) -> Self {
+        BalanceResponse {
+            balance: balance.amount_minor,
+            currency: balance.currency,
+            formatted: balance.to_string(),
+            available_balance: Some(available.amount_minor),
+            available_formatted: Some(available.to_string()),
+            as_of: None,
+        }
+    }
+
+    pub fn historical(balance: Money, as_of: DateTime<Utc>) -> Self {
+        BalanceResponse {
+            balance: balance.amount_minor,
+            currency: balance.currency,
+            formatted: balance.to_string(),
+            available_balance: None,
+            available_formatted: None,
+            as_of: Some(as_of),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceQuery {
+    /// RFC 3339 timestamp, e.g. `2025-01-31T23:59:59Z`.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// --- Deposit / Withdraw DTO ---
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransferRequest {
     pub id: u32,
-    pub amount: Kobo,
+    /// Minor units or a decimal string, in the account's currency.
+    pub amount: Amount,
     pub description: Option<String>,
+    /// Customer giving a withdrawal instruction on a joint account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory: Option<u64>,
 }
 
-/// --- Transfer Between Accounts DTO ---
+/// Account with its current balances and the liens in force.
+#[derive(Debug, Serialize)]
+pub struct AccountDetailResponse {
+    #[serde(flatten)]
+    pub account: Account,
+    /// Balance less active holds and liens.
+    pub available_balance: Money,
+    /// What the account can currently be debited by, overdraft included.
+    pub spendable_balance: Money,
+    pub active_liens: Vec<Lien>,
+}
+
+/// --- Lien DTOs ---
 #[derive(Debug, Deserialize)]
+pub struct PlaceLienRequest {
+    /// Minor units or a decimal string, in the account's currency.
+    pub amount: Amount,
+    pub reason: String,
+    pub reference: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AmendLienRequest {
+    pub amount: Option<Amount>,
+    pub reason: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// --- Account Status DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct StatusChangeRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FreezeRequest {
+    pub scope: FreezeScope,
+    pub reason: String,
+}
+
+/// --- Customer DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct AttachAccountRequest {
+    pub account_id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomerKycStatusRequest {
+    pub status: KycStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerAccountsResponse {
+    pub customer: Customer,
+    pub accounts: Vec<Account>,
+    /// Total balance of the accounts, one entry per currency.
+    pub balances: Vec<Money>,
+}
+
+/// --- KYC DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct UpgradeTierRequest {
+    pub tier: KycTier,
+}
+
+/// --- Authorization Hold DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeRequest {
+    pub id: u32,
+    /// Minor units or a decimal string, in the account's currency.
+    pub amount: Amount,
+    pub description: Option<String>,
+    /// Lifetime of the hold; defaults to the ledger's `hold_ttl_secs`.
+    pub expires_in_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaptureRequest {
+    /// Amount to capture; the full hold when omitted.
+    pub amount: Option<Amount>,
+    pub description: Option<String>,
+}
+
+/// --- Transfer Between Accounts DTO ---
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TransferBetweenRequest {
     pub from: u32,
     pub to: u32,
-    pub amount: Kobo,
+    /// Minor units or a decimal string, in the source account's currency.
+    pub amount: Amount,
     pub description: Option<String>,
+    /// Customer giving the instruction when the source is a joint account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory: Option<u64>,
+}
+
+/// --- Mandate & Instruction DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct SetMandateRequest {
+    /// Customer ids of the signatories.
+    pub signatories: Vec<u64>,
+    pub rule: MandateRule,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApproveInstructionRequest {
+    pub signatory: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RejectInstructionRequest {
+    pub signatory: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListInstructionsQuery {
+    pub account_id: Option<u32>,
+    pub status: Option<InstructionStatus>,
+}
+
+/// A debit held for signatory approval instead of being posted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingInstructionResponse {
+    pub instruction_id: u64,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// --- Maker-Checker DTOs ---
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalRequest {
+    pub description: Option<String>,
+    pub entries: Vec<TransactionEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListApprovalsQuery {
+    pub status: Option<ApprovalStatus>,
+}
+
+/// An operation queued for a second operator's approval.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingApprovalResponse {
+    pub approval_id: u64,
+}
+
+/// Result of a debit or journal: posted, or pending approval.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SubmissionResponse {
+    /// A posted FX transfer. Listed first, as any posted response would
+    /// also read as a plain `Posted` one.
+    Converted(FxTransferResponse),
+    Posted(TxResponse),
+    Pending(PendingInstructionResponse),
+    AwaitingApproval(PendingApprovalResponse),
+}
+
+/// --- Standing Order DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct CreateStandingOrderRequest {
+    pub from: u32,
+    pub to: u32,
+    /// Minor units or a decimal string, in the source account's currency.
+    pub amount: Amount,
+    pub description: Option<String>,
+    pub frequency: Frequency,
+    /// First occurrence; immediately when omitted.
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default = "default_failure_policy")]
+    pub policy: FailurePolicy,
+}
+
+fn default_failure_policy() -> FailurePolicy {
+    FailurePolicy::Skip
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListStandingOrdersQuery {
+    pub account: Option<u32>,
+}
+
+/// --- Product & Interest DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct AssignProductRequest {
+    /// Product code, or null to remove the account from its product.
+    pub product: Option<String>,
 }
 
-/// --- Transaction response ---
 #[derive(Debug, Serialize)]
+pub struct InterestStatusResponse {
+    /// Terms in force: the account's own, else its product's.
+    pub terms: Option<InterestTerms>,
+    /// Interest accrued and not yet capitalized.
+    pub accrued: Money,
+    pub accrued_through: Option<NaiveDate>,
+    pub last_capitalized: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InterestRunRequest {
+    /// Accrue up to and including this day.
+    pub date: NaiveDate,
+}
+
+/// --- Fee DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct CreateFeeRuleRequest {
+    pub operation: FeeOperation,
+    #[serde(default)]
+    pub product: Option<String>,
+    pub currency: Currency,
+    pub schedule: FeeSchedule,
+    #[serde(default)]
+    pub min: Option<i64>,
+    #[serde(default)]
+    pub cap: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeeQuoteRequest {
+    pub operation: FeeOperation,
+    /// Account paying the fee: the source of the transfer or withdrawal.
+    pub account_id: u32,
+    /// Minor units or a decimal string, in the account's currency.
+    pub amount: Amount,
+}
+
+/// --- Levy DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct CreateLevyRuleRequest {
+    pub kind: LevyKind,
+    pub currency: Currency,
+    pub effective_from: NaiveDate,
+    /// Smallest base amount the levy applies to, in minor units.
+    #[serde(default)]
+    pub threshold: i64,
+    #[serde(default)]
+    pub flat: i64,
+    #[serde(default)]
+    pub bps: u32,
+}
+
+/// --- Report DTOs ---
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BalanceSheetQuery {
+    /// Defaults to now.
+    pub as_of: Option<DateTime<Utc>>,
+    /// Also restate the totals in this currency at mid rates.
+    pub base: Option<Currency>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncomeStatementQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Also restate the totals in this currency at mid rates.
+    pub base: Option<Currency>,
+}
+
+/// --- Transaction response ---
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TxResponse {
     pub tx_id: u64,
 }
 
+/// --- Admin DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct SystemAccountRequest {
+    pub role: SystemRole,
+    pub currency: Currency,
+    pub account_id: u32,
+}
+
+/// --- FX DTOs ---
+#[derive(Debug, Deserialize)]
+pub struct FxRateRequest {
+    pub base: Currency,
+    pub quote: Currency,
+    pub mid: f64,
+    #[serde(default)]
+    pub spread_bps: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxTransferResponse {
+    pub tx_id: u64,
+    #[serde(flatten)]
+    pub conversion: FxConversion,
+}
+
 /// --- Save / Load DTO ---
 #[derive(Debug, Deserialize)]
 pub struct SaveLoadRequest {
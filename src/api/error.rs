@@ -0,0 +1,75 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::domain::error::LedgerError;
+
+/// RFC 7807 problem details body returned for every failed request.
+#[derive(Debug, Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Machine-readable error code, see `LedgerError::code`.
+    pub code: &'static str,
+}
+
+fn status_for(err: &LedgerError) -> StatusCode {
+    match err {
+        LedgerError::NotFound { .. } => StatusCode::NOT_FOUND,
+        LedgerError::Closed(_) | LedgerError::Restricted { .. } | LedgerError::BalanceNotZero { .. } => {
+            StatusCode::CONFLICT
+        }
+        LedgerError::InsufficientFunds { .. }
+        | LedgerError::LimitExceeded { .. }
+        | LedgerError::Unbalanced { .. }
+        | LedgerError::CurrencyMismatch { .. }
+        | LedgerError::Overflow(_)
+        | LedgerError::IdempotencyKeyReused(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        LedgerError::Validation(_) => StatusCode::BAD_REQUEST,
+        LedgerError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        LedgerError::Persistence(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn title_for(err: &LedgerError) -> &'static str {
+    match err {
+        LedgerError::NotFound { .. } => "Resource not found",
+        LedgerError::Closed(_) => "Account closed",
+        LedgerError::Restricted { .. } => "Account restricted",
+        LedgerError::InsufficientFunds { .. } => "Insufficient funds",
+        LedgerError::LimitExceeded { .. } => "Limit exceeded",
+        LedgerError::Unbalanced { .. } => "Unbalanced transaction",
+        LedgerError::CurrencyMismatch { .. } => "Currency mismatch",
+        LedgerError::Overflow(_) => "Numeric overflow",
+        LedgerError::BalanceNotZero { .. } => "Balance not zero",
+        LedgerError::Validation(_) => "Invalid request",
+        LedgerError::Persistence(_) => "Persistence failure",
+        LedgerError::IdempotencyKeyReused(_) => "Idempotency key reused",
+        LedgerError::Unauthorized(_) => "Unauthorized",
+    }
+}
+
+impl IntoResponse for LedgerError {
+    fn into_response(self) -> Response {
+        let status = status_for(&self);
+        let body = ProblemDetails {
+            type_uri: format!("urn:transaction-ledger:error:{}", self.code()),
+            title: title_for(&self),
+            status: status.as_u16(),
+            detail: self.to_string(),
+            code: self.code(),
+        };
+        (
+            status,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            Json(body),
+        )
+            .into_response()
+    }
+}
@@ -1,55 +1,461 @@
-use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use axum_macros::debug_handler;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 
 use crate::{
     api::dto::*,
-    domain::{account::Account, ledger::Ledger, transaction::Transaction},
+    domain::{account::{Account, AccountType, SystemRole}, currency::Currency, customer::{Customer, CustomerDetails}, error::LedgerError, fee::{FeeQuote, FeeRule}, hierarchy::RollupNode, hold::Hold, idempotency, interest::{AccrualRun, InterestTerms}, levy::LevyRule, lien::Lien, approval::ApprovalRequest, mandate::{Instruction, Operation, Submission}, product::Product, fx::{FxRate, RateTable}, ledger::{Ledger, LedgerSettings, OpeningOptions, SettingsChange}, money::{Amount, Money}, reports::{BalanceSheet, IncomeStatement}, standing_order::StandingOrder, transaction::{Charge, Transaction}},
     state::AppState,
 };
 
-/// --- Account Handlers ---
+/// Resolves a client-supplied amount in the currency of `account_id`.
+fn amount_for(ledger: &Ledger, account_id: u32, amount: &Amount) -> Result<Money, LedgerError> {
+    amount.to_money(ledger.account_currency(account_id)?)
+}
+
+/// Fees and other charges collected by `tx_id`, for event payloads.
+fn charges_of(ledger: &Ledger, tx_id: u64) -> Vec<Charge> {
+    ledger.transaction(tx_id).map(|tx| tx.charges.clone()).unwrap_or_default()
+}
+
+/// An `Idempotency-Key` header together with the fingerprint of the request it came with.
+struct Idempotency {
+    key: String,
+    fingerprint: String,
+}
+
+fn idempotency<T: Serialize>(headers: &HeaderMap, operation: &str, request: &T) -> Result<Option<Idempotency>, LedgerError> {
+    let Some(value) = headers.get("idempotency-key") else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| LedgerError::validation("Idempotency-Key must be visible ASCII"))?
+        .trim();
+    if key.is_empty() || key.len() > 255 {
+        return Err(LedgerError::validation("Idempotency-Key must be 1 to 255 characters"));
+    }
+    Ok(Some(Idempotency {
+        key: key.to_string(),
+        fingerprint: idempotency::fingerprint(operation, request)?,
+    }))
+}
+
+/// Operator authenticated by the request's bearer token, see `OperatorTokens::authenticate`.
+fn operator(state: &AppState, headers: &HeaderMap) -> Result<String, LedgerError> {
+    optional_operator(state, headers)?
+        .ok_or_else(|| LedgerError::Unauthorized("An operator bearer token is required".into()))
+}
+
+/// The authenticated operator when the caller sent credentials.
+fn optional_operator(state: &AppState, headers: &HeaderMap) -> Result<Option<String>, LedgerError> {
+    state.operators.authenticate(headers)
+}
+
+/// The original response when this request is a retry of one already processed.
+fn replay<T: DeserializeOwned>(ledger: &mut Ledger, idem: Option<&Idempotency>) -> Result<Option<T>, LedgerError> {
+    let Some(idem) = idem else { return Ok(None) };
+    match ledger.idempotent_response(&idem.key, &idem.fingerprint)? {
+        Some(stored) => serde_json::from_value(stored)
+            .map(Some)
+            .map_err(|e| LedgerError::Persistence(format!("Corrupt idempotency record: {}", e))),
+        None => Ok(None),
+    }
+}
+
+fn remember<T: Serialize>(ledger: &mut Ledger, idem: Option<Idempotency>, response: &T) {
+    if let Some(idem) = idem {
+        let stored = serde_json::to_value(response).expect("responses serialize to JSON");
+        ledger.remember_response(idem.key, idem.fingerprint, stored);
+    }
+}
+
+// --- Account Handlers ---
 
 #[debug_handler]
 pub async fn create_account_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<CreateAccountRequest>,
-)-> Result<Json<CreateAccountResponse>,StatusCode> {
+)-> Result<Json<CreateAccountResponse>,LedgerError> {
+    let idem = idempotency(&headers, "create_account", &payload)?;
     let mut ledger = state.ledger.write().await;
-    match ledger.create_account(
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(Json(response));
+    }
+    let options = OpeningOptions {
+        parent_id: payload.parent_id,
+        pending: payload.pending,
+        customer_id: payload.customer_id,
+    };
+    let id = ledger.open_account(
         payload.owner, 
-        payload.initial,
-        payload.currency.clone(), 
+        payload.initial.to_money(payload.currency)?,
         payload.bank_name.clone(), 
-        payload.bank_code.clone()
-    ) {
-        Ok(id) =>{ 
-            let account = ledger.accounts.get(&id).unwrap();
-            Ok(Json(CreateAccountResponse { 
-            id,
-            bank_name: account.bank_name.clone(),
-            bank_code: account.bank_code.clone(),
-            account_number: account.account_number.clone(),
-            currency: account.currency.clone(),  
-        }))},
-        Err(_) => Err(StatusCode::BAD_REQUEST),
-    } 
+        payload.bank_code.clone(),
+        options,
+    )?;
+    let account = ledger.accounts.get(&id).ok_or(LedgerError::account_not_found(id))?;
+    let response = CreateAccountResponse { 
+        id,
+        bank_name: account.bank_name.clone(),
+        bank_code: account.bank_code.clone(),
+        account_number: account.account_number.clone(),
+        currency: account.currency,
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(Json(response))
 }
 
 
-pub async fn get_balance_handler(
+pub async fn get_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<AccountDetailResponse>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let account = ledger.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+    Ok(Json(AccountDetailResponse {
+        available_balance: ledger.available_balance(account_id)?,
+        spendable_balance: ledger.spendable_balance(account_id)?,
+        active_liens: account.active_liens().cloned().collect(),
+        account: account.clone(),
+    }))
+}
+
+// --- Lien Handlers ---
+
+pub async fn place_lien_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<PlaceLienRequest>,
+) -> Result<Json<Lien>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let amount = amount_for(&ledger, account_id, &req.amount)?;
+    let id = ledger.place_lien(account_id, amount, req.reason, req.reference, req.expires_at)?;
+    let lien = ledger.lien(account_id, id)?.clone();
+    let event = serde_json::json!({
+        "type": "lien_placed",
+        "account_id": account_id,
+        "lien_id": id,
+        "amount": amount.amount_minor,
+        "currency": amount.currency,
+        "reference": lien.reference
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    Ok(Json(lien))
+}
+
+pub async fn list_liens_handler(
     State(state): State<AppState>,
     Path(account_id): Path<u32>,
-) -> Result<Json<HashMap<&'static str, i64>>, (StatusCode, String)> {
+) -> Result<Json<Vec<Lien>>, LedgerError> {
     let ledger = state.ledger.read().await;
-    match ledger.get_balance(account_id) {
-        Some(bal) => {
-            let mut map = HashMap::new();
-            map.insert("balance", bal);
-            Ok(Json(map))
+    let account = ledger.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+    Ok(Json(account.liens.clone()))
+}
+
+pub async fn get_lien_handler(
+    State(state): State<AppState>,
+    Path((account_id, lien_id)): Path<(u32, u64)>,
+) -> Result<Json<Lien>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.lien(account_id, lien_id)?.clone()))
+}
+
+pub async fn amend_lien_handler(
+    State(state): State<AppState>,
+    Path((account_id, lien_id)): Path<(u32, u64)>,
+    Json(req): Json<AmendLienRequest>,
+) -> Result<Json<Lien>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let amount = req.amount.as_ref().map(|a| amount_for(&ledger, account_id, a)).transpose()?;
+    ledger.amend_lien(account_id, lien_id, amount, req.reason, req.expires_at)?;
+    Ok(Json(ledger.lien(account_id, lien_id)?.clone()))
+}
+
+pub async fn lift_lien_handler(
+    State(state): State<AppState>,
+    Path((account_id, lien_id)): Path<(u32, u64)>,
+) -> Result<Json<Lien>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.lift_lien(account_id, lien_id)?;
+    let event = serde_json::json!({
+        "type": "lien_lifted",
+        "account_id": account_id,
+        "lien_id": lien_id
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    Ok(Json(ledger.lien(account_id, lien_id)?.clone()))
+}
+
+pub async fn enforce_lien_handler(
+    State(state): State<AppState>,
+    Path((account_id, lien_id)): Path<(u32, u64)>,
+    headers: HeaderMap,
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, &format!("enforce_lien {}/{}", account_id, lien_id), &())?;
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let operation = Operation::LienEnforcement { account_id, lien_id };
+    let txid = match ledger.submit(operation, None, None, maker.as_deref())? {
+        Submission::Posted { tx_id } => tx_id,
+        Submission::AwaitingApproval { approval_id } => {
+            let response = awaiting_approval(&state, &ledger, approval_id).await?;
+            remember(&mut ledger, idem, &response);
+            return Ok(submission_response(response));
         }
-        None => Err((StatusCode::NOT_FOUND, "Account not found".into())),
+        other => unreachable!("lien enforcement is posted or queued, not {:?}", other),
+    };
+    let amount = ledger.lien(account_id, lien_id)?.amount;
+    let event = serde_json::json!({
+        "type": "lien_enforced",
+        "account_id": account_id,
+        "lien_id": lien_id,
+        "amount": amount.amount_minor,
+        "currency": amount.currency,
+        "tx_id": txid
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    let response = SubmissionResponse::Posted(TxResponse { tx_id: txid });
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
+}
+
+// --- Account Status Handlers ---
+
+/// Publishes the latest status change of `account_id` and returns the account.
+async fn status_changed(state: &AppState, ledger: &Ledger, account_id: u32) -> Json<Account> {
+    let account = ledger.accounts[&account_id].clone();
+    if let Some(change) = account.status_history.last() {
+        let event = serde_json::json!({
+            "type": "account_status",
+            "account_id": account_id,
+            "from": change.from,
+            "to": change.to,
+            "reason": change.reason,
+            "at": change.at
+        });
+        state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    }
+    Json(account)
+}
+
+pub async fn freeze_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<FreezeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.freeze_account(account_id, req.scope, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+pub async fn unfreeze_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.unfreeze_account(account_id, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+pub async fn close_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.close_account(account_id, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+pub async fn reopen_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.reopen_account(account_id, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+pub async fn activate_account_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.activate_account(account_id, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+pub async fn mark_dormant_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.mark_dormant(account_id, &req.reason)?;
+    Ok(status_changed(&state, &ledger, account_id).await)
+}
+
+// --- Customer Handlers ---
+
+fn customer_accounts(ledger: &Ledger, customer_id: u64) -> Result<CustomerAccountsResponse, LedgerError> {
+    Ok(CustomerAccountsResponse {
+        customer: ledger.customer(customer_id)?.clone(),
+        accounts: ledger.customer_accounts(customer_id)?.into_iter().cloned().collect(),
+        balances: ledger.customer_balances(customer_id)?,
+    })
+}
+
+pub async fn create_customer_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CustomerDetails>,
+) -> Result<Json<Customer>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let id = ledger.create_customer(req)?;
+    Ok(Json(ledger.customer(id)?.clone()))
+}
+
+pub async fn list_customers_handler(State(state): State<AppState>) -> Json<Vec<Customer>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.customers.values().cloned().collect())
+}
+
+pub async fn get_customer_handler(
+    State(state): State<AppState>,
+    Path(customer_id): Path<u64>,
+) -> Result<Json<Customer>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.customer(customer_id)?.clone()))
+}
+
+pub async fn set_customer_kyc_status_handler(
+    State(state): State<AppState>,
+    Path(customer_id): Path<u64>,
+    Json(req): Json<CustomerKycStatusRequest>,
+) -> Result<Json<Customer>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_customer_kyc_status(customer_id, req.status)?;
+    Ok(Json(ledger.customer(customer_id)?.clone()))
+}
+
+pub async fn attach_account_handler(
+    State(state): State<AppState>,
+    Path(customer_id): Path<u64>,
+    Json(req): Json<AttachAccountRequest>,
+) -> Result<Json<CustomerAccountsResponse>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.attach_account(customer_id, req.account_id)?;
+    Ok(Json(customer_accounts(&ledger, customer_id)?))
+}
+
+pub async fn list_customer_accounts_handler(
+    State(state): State<AppState>,
+    Path(customer_id): Path<u64>,
+) -> Result<Json<CustomerAccountsResponse>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(customer_accounts(&ledger, customer_id)?))
+}
+
+// --- KYC Handlers ---
+
+pub async fn upgrade_tier_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    headers: HeaderMap,
+    Json(req): Json<UpgradeTierRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let by = operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    ledger.upgrade_kyc_tier(account_id, req.tier, &by)?;
+    let account = ledger.accounts[&account_id].clone();
+    let change = account.kyc_history.last().expect("upgrade just recorded");
+    let event = serde_json::json!({
+        "type": "kyc_tier_upgraded",
+        "account_id": account_id,
+        "from": change.from,
+        "to": change.to,
+        "by": change.by,
+        "at": change.at
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    Ok(Json(account))
+}
+
+pub async fn create_group_account_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CreateGroupAccountRequest>,
+) -> Result<Json<CreateGroupAccountResponse>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let id = ledger.create_group_account(req.name, req.account_type, req.currency, req.parent_id)?;
+    Ok(Json(CreateGroupAccountResponse { id }))
+}
+
+pub async fn set_parent_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<SetParentRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_parent(account_id, req.parent_id)?;
+    let account = ledger.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+    Ok(Json(account.clone()))
+}
+
+pub async fn set_overdraft_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<OverdraftRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let limit = amount_for(&ledger, account_id, &req.limit)?;
+    ledger.set_overdraft_limit(account_id, limit)?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn remove_overdraft_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let currency = ledger.account_currency(account_id)?;
+    ledger.set_overdraft_limit(account_id, Money::zero(currency))?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn rollup_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<RollupNode>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.rollup(account_id)?))
+}
+
+pub async fn get_balance_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Query(q): Query<BalanceQuery>,
+) -> Result<Json<BalanceResponse>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    if let Some(as_of) = q.as_of {
+        let bal = ledger.balance_as_of(account_id, as_of)?;
+        return Ok(Json(BalanceResponse::historical(bal, as_of)));
     }
+    let bal = ledger.get_balance(account_id).ok_or(LedgerError::account_not_found(account_id))?;
+    let available = ledger.available_balance(account_id)?;
+    Ok(Json(BalanceResponse::new(bal, available)))
 }
 
 
@@ -60,98 +466,404 @@ pub async fn find_account_by_owner_handler(
     let ledger = state.ledger.read().await;
 
     let accounts: Vec<Account> = match query.owner {
-        Some(ref owner) => ledger
-            .accounts
-            .values()
-            .filter(|a| a.owner == *owner)
-            .cloned()
-            .collect(),
+        Some(ref owner) => ledger.find_accounts_by_owner(owner).into_iter().cloned().collect(),
         None => ledger.accounts.values().cloned().collect(),
     };
 
     Json(accounts)
 }
 
-/// --- Transaction Handlers ---
+// --- Transaction Handlers ---
 
 #[debug_handler]
 pub async fn deposit_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<TransferRequest>,
-) -> Result<Json<TxResponse>, (StatusCode, String)> {
+) -> Result<Json<TxResponse>, LedgerError> {
+    let idem = idempotency(&headers, "deposit", &req)?;
     let mut ledger = state.ledger.write().await;
-    match ledger.deposit(req.id, req.amount, req.description.clone()) {
-        Ok(txid) => {
-            let event = serde_json::json!({
-                "type": "deposit",
-                "account_id": req.id,
-                "amount": req.amount,
-                "description": req.description,
-                "tx_id": txid
-            }
-            );
-
-            state.kafka.send("transactions",&req.id.to_string(),&event.to_string()).await;
-            Ok(Json(TxResponse { tx_id: txid }))
-        }
-        Err(e) => Err((StatusCode::BAD_REQUEST, e)),
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(Json(response));
+    }
+    let amount = amount_for(&ledger, req.id, &req.amount)?;
+    let txid = ledger.deposit(req.id, amount, req.description.clone())?;
+    let event = serde_json::json!({
+        "type": "deposit",
+        "account_id": req.id,
+        "amount": amount.amount_minor,
+        "currency": amount.currency,
+        "description": req.description,
+        "tx_id": txid
     }
+    );
+
+    state.kafka.send("transactions",&req.id.to_string(),&event.to_string()).await;
+    let response = TxResponse { tx_id: txid };
+    remember(&mut ledger, idem, &response);
+    Ok(Json(response))
 }
 
 
 pub async fn withdraw_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<TransferRequest>,
-) -> Result<Json<TxResponse>, (StatusCode, String)> {
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, "withdraw", &req)?;
+    let maker = optional_operator(&state, &headers)?;
     let mut ledger = state.ledger.write().await;
-    match ledger.withdraw(req.id, req.amount, req.description.clone()) {
-        Ok(txid) => {
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let amount = amount_for(&ledger, req.id, &req.amount)?;
+    let operation = Operation::Withdrawal { account_id: req.id, amount };
+    let response = match ledger.submit(operation, req.description.clone(), req.signatory, maker.as_deref())? {
+        Submission::Posted { tx_id: txid } => {
             // ✅ Send Kafka event
             let event = serde_json::json!({
                 "type": "withdrawal",
                 "account_id": req.id,
-                "amount": req.amount,
+                "amount": amount.amount_minor,
+                "currency": amount.currency,
                 "description": req.description,
+                "charges": charges_of(&ledger, txid),
                 "tx_id": txid
             });
             state.kafka.send("transactions", &req.id.to_string(), &event.to_string()).await;
-
-            Ok(Json(TxResponse { tx_id: txid }))
-        },
-        Err(e) => Err((StatusCode::BAD_REQUEST, e)),
-    }
+            SubmissionResponse::Posted(TxResponse { tx_id: txid })
+        }
+        Submission::Pending { instruction_id } => pending_instruction(&state, &ledger, instruction_id).await?,
+        Submission::AwaitingApproval { approval_id } => awaiting_approval(&state, &ledger, approval_id).await?,
+        Submission::Held { .. } | Submission::Scheduled { .. } => unreachable!("debits are posted, not held or scheduled"),
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
 }
 
 
 pub async fn transfer_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<TransferBetweenRequest>,
-) -> Result<Json<TxResponse>, (StatusCode, String)> {
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, "transfer", &req)?;
+    let maker = optional_operator(&state, &headers)?;
     let mut ledger = state.ledger.write().await;
-    match ledger.transfer(req.from, req.to, req.amount, req.description.clone()) {
-        Ok(txid) =>{ 
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let amount = amount_for(&ledger, req.from, &req.amount)?;
+    let operation = Operation::Transfer { from: req.from, to: req.to, amount };
+    let response = match ledger.submit(operation, req.description.clone(), req.signatory, maker.as_deref())? {
+        Submission::Posted { tx_id: txid } => {
             // ✅ Send Kafka event
             let event = serde_json::json!({
                 "type": "deposit",
                 "from_id": req.from,
                 "to_id": req.to,
-                "amount": req.amount,
+                "amount": amount.amount_minor,
+                "currency": amount.currency,
                 "description": req.description,
+                "charges": charges_of(&ledger, txid),
                 "tx_id": txid
             });
             let key = format!("{}->{}", req.from, req.to);
             state.kafka.send("transactions", &key, &event.to_string()).await;
-            Ok(Json(TxResponse { tx_id: txid }))
-        },
-        Err(e) => Err((StatusCode::BAD_REQUEST, e)),
+            SubmissionResponse::Posted(TxResponse { tx_id: txid })
+        }
+        Submission::Pending { instruction_id } => pending_instruction(&state, &ledger, instruction_id).await?,
+        Submission::AwaitingApproval { approval_id } => awaiting_approval(&state, &ledger, approval_id).await?,
+        Submission::Held { .. } | Submission::Scheduled { .. } => unreachable!("debits are posted, not held or scheduled"),
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
+}
+
+/// 200 with the transaction once posted, 202 while it waits for approval.
+fn submission_response(response: SubmissionResponse) -> Response {
+    match response {
+        SubmissionResponse::Converted(posted) => Json(posted).into_response(),
+        SubmissionResponse::Posted(posted) => Json(posted).into_response(),
+        SubmissionResponse::Pending(pending) => (StatusCode::ACCEPTED, Json(pending)).into_response(),
+        SubmissionResponse::AwaitingApproval(queued) => (StatusCode::ACCEPTED, Json(queued)).into_response(),
+    }
+}
+
+async fn awaiting_approval(state: &AppState, ledger: &Ledger, approval_id: u64) -> Result<SubmissionResponse, LedgerError> {
+    publish_approval(state, ledger.approval_request(approval_id)?).await;
+    Ok(SubmissionResponse::AwaitingApproval(PendingApprovalResponse { approval_id }))
+}
+
+/// Publishes the state of a maker-checker request after it changes.
+async fn publish_approval(state: &AppState, request: &ApprovalRequest) {
+    let event = serde_json::json!({
+        "type": "approval_request",
+        "approval_id": request.id,
+        "operation": request.operation,
+        "status": request.status,
+        "maker": request.maker,
+        "checker": request.checker,
+        "rejection_reason": request.rejection_reason,
+        "tx_id": request.tx_id,
+        "instruction_id": request.instruction_id,
+        "hold_id": request.hold_id,
+        "standing_order_id": request.standing_order_id
+    });
+    state.kafka.send("transactions", &request.id.to_string(), &event.to_string()).await;
+}
+
+async fn pending_instruction(state: &AppState, ledger: &Ledger, instruction_id: u64) -> Result<SubmissionResponse, LedgerError> {
+    let instruction = ledger.instruction(instruction_id)?;
+    publish_instruction(state, instruction).await;
+    Ok(SubmissionResponse::Pending(PendingInstructionResponse {
+        instruction_id,
+        expires_at: instruction.expires_at,
+    }))
+}
+
+/// Publishes the state of a joint-account instruction after it changes.
+async fn publish_instruction(state: &AppState, instruction: &Instruction) {
+    let account_id = instruction.account_id;
+    let event = serde_json::json!({
+        "type": "instruction",
+        "instruction_id": instruction.id,
+        "account_id": account_id,
+        "operation": instruction.operation,
+        "status": instruction.status,
+        "approvals": instruction.approvals,
+        "tx_id": instruction.tx_id
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+}
+
+pub async fn journal_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<JournalRequest>,
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, "journal", &req)?;
+    let maker = operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let operation = Operation::Journal { entries: req.entries };
+    let response = match ledger.submit(operation, req.description.clone(), None, Some(&maker))? {
+        Submission::Posted { tx_id } => {
+            let event = serde_json::json!({
+                "type": "journal",
+                "description": req.description,
+                "maker": maker,
+                "tx_id": tx_id
+            });
+            state.kafka.send("transactions", &tx_id.to_string(), &event.to_string()).await;
+            SubmissionResponse::Posted(TxResponse { tx_id })
+        }
+        Submission::AwaitingApproval { approval_id } => awaiting_approval(&state, &ledger, approval_id).await?,
+        Submission::Pending { .. } => unreachable!("journals are not subject to mandates"),
+        Submission::Held { .. } | Submission::Scheduled { .. } => unreachable!("journals are posted, not held or scheduled"),
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
+}
+
+// --- Maker-Checker Handlers ---
+
+pub async fn list_approvals_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListApprovalsQuery>,
+) -> Json<Vec<ApprovalRequest>> {
+    let ledger = state.ledger.read().await;
+    let requests = ledger
+        .approval_requests
+        .values()
+        .filter(|r| query.status.is_none_or(|status| r.status == status))
+        .cloned()
+        .collect();
+    Json(requests)
+}
+
+pub async fn get_approval_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<ApprovalRequest>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.approval_request(id)?.clone()))
+}
+
+pub async fn approve_request_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Json<ApprovalRequest>, LedgerError> {
+    let checker = operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    let request = ledger.approve_request(id, &checker)?.clone();
+    publish_approval(&state, &request).await;
+    if let Some(instruction_id) = request.instruction_id {
+        publish_instruction(&state, ledger.instruction(instruction_id)?).await;
+    }
+    Ok(Json(request))
+}
+
+pub async fn reject_request_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    Json(req): Json<StatusChangeRequest>,
+) -> Result<Json<ApprovalRequest>, LedgerError> {
+    let checker = operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    let request = ledger.reject_request(id, &checker, &req.reason)?.clone();
+    publish_approval(&state, &request).await;
+    Ok(Json(request))
+}
+
+// --- Mandate & Instruction Handlers ---
+
+pub async fn set_mandate_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<SetMandateRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_mandate(account_id, req.signatories, req.rule)?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn clear_mandate_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.clear_mandate(account_id)?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn list_instructions_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListInstructionsQuery>,
+) -> Json<Vec<Instruction>> {
+    let ledger = state.ledger.read().await;
+    let instructions = ledger
+        .instructions
+        .values()
+        .filter(|i| query.account_id.is_none_or(|id| i.account_id == id))
+        .filter(|i| query.status.is_none_or(|status| i.status == status))
+        .cloned()
+        .collect();
+    Json(instructions)
+}
+
+pub async fn get_instruction_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Instruction>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.instruction(id)?.clone()))
+}
+
+pub async fn approve_instruction_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(req): Json<ApproveInstructionRequest>,
+) -> Result<Json<Instruction>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let instruction = ledger.approve_instruction(id, req.signatory)?.clone();
+    publish_instruction(&state, &instruction).await;
+    Ok(Json(instruction))
+}
+
+pub async fn reject_instruction_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(req): Json<RejectInstructionRequest>,
+) -> Result<Json<Instruction>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let instruction = ledger.reject_instruction(id, req.signatory, &req.reason)?.clone();
+    publish_instruction(&state, &instruction).await;
+    Ok(Json(instruction))
+}
+
+pub async fn fx_transfer_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<TransferBetweenRequest>,
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, "fx_transfer", &req)?;
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
     }
+    let amount = amount_for(&ledger, req.from, &req.amount)?;
+    let operation = Operation::FxTransfer { from: req.from, to: req.to, amount };
+    let response = match ledger.submit(operation, req.description.clone(), req.signatory, maker.as_deref())? {
+        Submission::Posted { tx_id: txid } => {
+            let conversion = ledger
+                .transaction(txid)
+                .and_then(|tx| tx.fx.clone())
+                .ok_or(LedgerError::NotFound { entity: "transaction", id: txid.to_string() })?;
+            let event = serde_json::json!({
+                "type": "fx_transfer",
+                "from_id": req.from,
+                "to_id": req.to,
+                "amount": amount.amount_minor,
+                "currency": amount.currency,
+                "description": req.description,
+                "fx": conversion,
+                "tx_id": txid
+            });
+            let key = format!("{}->{}", req.from, req.to);
+            state.kafka.send("transactions", &key, &event.to_string()).await;
+            SubmissionResponse::Converted(FxTransferResponse { tx_id: txid, conversion })
+        }
+        Submission::Pending { instruction_id } => pending_instruction(&state, &ledger, instruction_id).await?,
+        Submission::AwaitingApproval { approval_id } => awaiting_approval(&state, &ledger, approval_id).await?,
+        Submission::Held { .. } | Submission::Scheduled { .. } => unreachable!("debits are posted, not held or scheduled"),
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
 }
 
 #[debug_handler]
+pub async fn reverse_transaction_handler(
+    State(state): State<AppState>,
+    Path(tx_id): Path<u64>,
+    headers: HeaderMap,
+    Json(req): Json<ReverseRequest>,
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, &format!("reverse {}", tx_id), &req)?;
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let operation = Operation::Reversal { tx_id, reason: req.reason.clone() };
+    let response = match ledger.submit(operation, None, req.signatory, maker.as_deref())? {
+        Submission::Posted { tx_id: reversal_id } => {
+            let event = serde_json::json!({
+                "type": "reversal",
+                "original_tx_id": tx_id,
+                "reason": req.reason,
+                "tx_id": reversal_id
+            });
+            state.kafka.send("transactions", &tx_id.to_string(), &event.to_string()).await;
+            SubmissionResponse::Posted(TxResponse { tx_id: reversal_id })
+        }
+        Submission::Pending { instruction_id } => pending_instruction(&state, &ledger, instruction_id).await?,
+        Submission::AwaitingApproval { approval_id } => awaiting_approval(&state, &ledger, approval_id).await?,
+        Submission::Held { .. } | Submission::Scheduled { .. } => unreachable!("debits are posted, not held or scheduled"),
+    };
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
+}
+
 pub async fn list_transactions_handler(
     State(state): State<AppState>,
     Query(q): Query<ListTxQuery>,
-) -> Result<Json<Vec<Transaction>>, (StatusCode, String)> {
+) -> Result<Json<Vec<Transaction>>, LedgerError> {
     let ledger = state.ledger.read().await;
     if let Some(acc_id) = q.account {
         let txs = ledger
@@ -166,24 +878,412 @@ pub async fn list_transactions_handler(
 }
 
 
-/// --- Persistence Handlers (Save / Load) ---
+// --- Admin Handlers ---
+
+pub async fn list_system_accounts_handler(
+    State(state): State<AppState>,
+) -> Json<HashMap<SystemRole, HashMap<Currency, u32>>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.system_accounts.clone())
+}
+
+pub async fn set_system_account_handler(
+    State(state): State<AppState>,
+    Json(req): Json<SystemAccountRequest>,
+) -> Result<Json<HashMap<SystemRole, HashMap<Currency, u32>>>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_system_account(req.role, req.currency, req.account_id)?;
+    Ok(Json(ledger.system_accounts.clone()))
+}
+
+pub async fn get_settings_handler(State(state): State<AppState>) -> Json<LedgerSettings> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.settings.clone())
+}
+
+pub async fn update_settings_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(settings): Json<LedgerSettings>,
+) -> Result<Response, LedgerError> {
+    let operator = operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    match ledger.update_settings(settings, &operator)? {
+        Some(approval_id) => Ok(submission_response(awaiting_approval(&state, &ledger, approval_id).await?)),
+        None => Ok(Json(ledger.settings.clone()).into_response()),
+    }
+}
+
+pub async fn settings_history_handler(State(state): State<AppState>) -> Json<Vec<SettingsChange>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.settings_changes.clone())
+}
+
+// --- Authorization Hold Handlers ---
+
+pub async fn authorize_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AuthorizeRequest>,
+) -> Result<Response, LedgerError> {
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    let amount = amount_for(&ledger, req.id, &req.amount)?;
+    let operation = Operation::Hold { account_id: req.id, amount, ttl_secs: req.expires_in_secs };
+    let hold_id = match ledger.submit(operation, req.description, None, maker.as_deref())? {
+        Submission::Held { hold_id } => hold_id,
+        Submission::AwaitingApproval { approval_id } => {
+            return Ok(submission_response(awaiting_approval(&state, &ledger, approval_id).await?));
+        }
+        other => unreachable!("a hold is placed or queued, not {:?}", other),
+    };
+    let hold = ledger.holds[&hold_id].clone();
+    let event = serde_json::json!({
+        "type": "hold_authorized",
+        "account_id": req.id,
+        "hold_id": hold_id,
+        "amount": amount.amount_minor,
+        "currency": amount.currency,
+        "expires_at": hold.expires_at
+    });
+    state.kafka.send("transactions", &req.id.to_string(), &event.to_string()).await;
+    Ok(Json(hold).into_response())
+}
+
+pub async fn capture_hold_handler(
+    State(state): State<AppState>,
+    Path(hold_id): Path<u64>,
+    headers: HeaderMap,
+    Json(req): Json<CaptureRequest>,
+) -> Result<Response, LedgerError> {
+    let idem = idempotency(&headers, &format!("capture {}", hold_id), &req)?;
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    if let Some(response) = replay(&mut ledger, idem.as_ref())? {
+        return Ok(submission_response(response));
+    }
+    let hold = ledger.holds.get(&hold_id).ok_or(LedgerError::NotFound {
+        entity: "hold",
+        id: hold_id.to_string(),
+    })?;
+    let account_id = hold.account_id;
+    let amount = req.amount.map(|a| a.to_money(hold.amount.currency)).transpose()?;
+    let operation = Operation::Capture { hold_id, amount };
+    let txid = match ledger.submit(operation, req.description, None, maker.as_deref())? {
+        Submission::Posted { tx_id } => tx_id,
+        Submission::AwaitingApproval { approval_id } => {
+            let response = awaiting_approval(&state, &ledger, approval_id).await?;
+            remember(&mut ledger, idem, &response);
+            return Ok(submission_response(response));
+        }
+        other => unreachable!("a capture is posted or queued, not {:?}", other),
+    };
+    let captured = ledger.holds[&hold_id].captured.expect("hold was just captured");
+    let event = serde_json::json!({
+        "type": "hold_captured",
+        "account_id": account_id,
+        "hold_id": hold_id,
+        "amount": captured.amount_minor,
+        "currency": captured.currency,
+        "tx_id": txid
+    });
+    state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    let response = SubmissionResponse::Posted(TxResponse { tx_id: txid });
+    remember(&mut ledger, idem, &response);
+    Ok(submission_response(response))
+}
+
+pub async fn release_hold_handler(
+    State(state): State<AppState>,
+    Path(hold_id): Path<u64>,
+) -> Result<Json<Hold>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.release(hold_id)?;
+    let hold = ledger.holds[&hold_id].clone();
+    let event = serde_json::json!({
+        "type": "hold_released",
+        "account_id": hold.account_id,
+        "hold_id": hold_id
+    });
+    state.kafka.send("transactions", &hold.account_id.to_string(), &event.to_string()).await;
+    Ok(Json(hold))
+}
+
+pub async fn list_holds_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<Vec<Hold>>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    if !ledger.accounts.contains_key(&account_id) {
+        return Err(LedgerError::account_not_found(account_id));
+    }
+    ledger.expire_holds(chrono::Utc::now());
+    Ok(Json(ledger.holds_for_account(account_id).into_iter().cloned().collect()))
+}
+
+// --- Product & Interest Handlers ---
+
+pub async fn list_products_handler(State(state): State<AppState>) -> Json<Vec<Product>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.products.values().cloned().collect())
+}
+
+pub async fn upsert_product_handler(
+    State(state): State<AppState>,
+    Json(product): Json<Product>,
+) -> Result<Json<Product>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.upsert_product(product.clone())?;
+    Ok(Json(product))
+}
+
+pub async fn assign_product_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(req): Json<AssignProductRequest>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.assign_product(account_id, req.product)?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn get_interest_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<InterestStatusResponse>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let terms = ledger.interest_terms(account_id)?;
+    let currency = ledger.account_currency(account_id)?;
+    let accrual = ledger.interest_accruals.get(&account_id);
+    Ok(Json(InterestStatusResponse {
+        terms,
+        accrued: accrual.map_or(Money::zero(currency), |a| a.accrued()),
+        accrued_through: accrual.map(|a| a.accrued_through),
+        last_capitalized: accrual.and_then(|a| a.last_capitalized),
+    }))
+}
+
+pub async fn set_interest_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+    Json(terms): Json<InterestTerms>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_account_interest(account_id, Some(terms))?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn clear_interest_handler(
+    State(state): State<AppState>,
+    Path(account_id): Path<u32>,
+) -> Result<Json<Account>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.set_account_interest(account_id, None)?;
+    Ok(Json(ledger.accounts[&account_id].clone()))
+}
+
+pub async fn run_interest_handler(
+    State(state): State<AppState>,
+    Json(req): Json<InterestRunRequest>,
+) -> Json<AccrualRun> {
+    let mut ledger = state.ledger.write().await;
+    Json(ledger.accrue_interest(req.date))
+}
+
+// --- Fee Handlers ---
+
+pub async fn list_fee_rules_handler(State(state): State<AppState>) -> Json<Vec<FeeRule>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.fee_rules.values().cloned().collect())
+}
+
+pub async fn create_fee_rule_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CreateFeeRuleRequest>,
+) -> Result<Json<FeeRule>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let id = ledger.add_fee_rule(req.operation, req.product, req.currency, req.schedule, req.min, req.cap)?;
+    Ok(Json(ledger.fee_rules[&id].clone()))
+}
+
+pub async fn delete_fee_rule_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<FeeRule>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    Ok(Json(ledger.remove_fee_rule(id)?))
+}
+
+pub async fn quote_fee_handler(
+    State(state): State<AppState>,
+    Json(req): Json<FeeQuoteRequest>,
+) -> Result<Json<FeeQuote>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let amount = amount_for(&ledger, req.account_id, &req.amount)?;
+    Ok(Json(ledger.quote_fee(req.operation, req.account_id, amount)?))
+}
+
+// --- Levy Handlers ---
+
+pub async fn list_levy_rules_handler(State(state): State<AppState>) -> Json<Vec<LevyRule>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.levy_rules.clone())
+}
+
+pub async fn create_levy_rule_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CreateLevyRuleRequest>,
+) -> Result<Json<LevyRule>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    let id = ledger.add_levy_rule(req.kind, req.currency, req.effective_from, req.threshold, req.flat, req.bps)?;
+    let rule = ledger.levy_rules.iter().find(|r| r.id == id).expect("rule just added");
+    Ok(Json(rule.clone()))
+}
+
+pub async fn delete_levy_rule_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<LevyRule>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    Ok(Json(ledger.remove_levy_rule(id)?))
+}
+
+// --- Standing Order Handlers ---
+
+pub async fn create_standing_order_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateStandingOrderRequest>,
+) -> Result<Response, LedgerError> {
+    let maker = optional_operator(&state, &headers)?;
+    let mut ledger = state.ledger.write().await;
+    let amount = amount_for(&ledger, req.from, &req.amount)?;
+    let operation = Operation::StandingOrder {
+        from: req.from,
+        to: req.to,
+        amount,
+        frequency: req.frequency,
+        start: req.start.unwrap_or_else(chrono::Utc::now),
+        end: req.end,
+        policy: req.policy,
+    };
+    match ledger.submit(operation, req.description, None, maker.as_deref())? {
+        Submission::Scheduled { standing_order_id } => {
+            Ok(Json(ledger.standing_orders[&standing_order_id].clone()).into_response())
+        }
+        Submission::AwaitingApproval { approval_id } => {
+            Ok(submission_response(awaiting_approval(&state, &ledger, approval_id).await?))
+        }
+        other => unreachable!("a standing order is scheduled or queued, not {:?}", other),
+    }
+}
+
+pub async fn list_standing_orders_handler(
+    State(state): State<AppState>,
+    Query(q): Query<ListStandingOrdersQuery>,
+) -> Json<Vec<StandingOrder>> {
+    let ledger = state.ledger.read().await;
+    let orders = match q.account {
+        Some(account_id) => ledger.standing_orders_for_account(account_id).into_iter().cloned().collect(),
+        None => ledger.standing_orders.values().cloned().collect(),
+    };
+    Json(orders)
+}
+
+pub async fn get_standing_order_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<StandingOrder>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.standing_order(id)?.clone()))
+}
+
+pub async fn pause_standing_order_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<StandingOrder>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.pause_standing_order(id)?;
+    Ok(Json(ledger.standing_orders[&id].clone()))
+}
+
+pub async fn resume_standing_order_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<StandingOrder>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.resume_standing_order(id, chrono::Utc::now())?;
+    Ok(Json(ledger.standing_orders[&id].clone()))
+}
+
+pub async fn cancel_standing_order_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<StandingOrder>, LedgerError> {
+    let mut ledger = state.ledger.write().await;
+    ledger.cancel_standing_order(id)?;
+    Ok(Json(ledger.standing_orders[&id].clone()))
+}
+
+// --- FX Rate Handlers ---
+
+pub async fn list_fx_rates_handler(State(state): State<AppState>) -> Json<Vec<FxRate>> {
+    let ledger = state.ledger.read().await;
+    Json(ledger.fx_rates.rates.clone())
+}
+
+pub async fn update_fx_rates_handler(
+    State(state): State<AppState>,
+    Json(req): Json<Vec<FxRateRequest>>,
+) -> Result<Json<Vec<FxRate>>, LedgerError> {
+    // Validate the whole batch before touching the live table.
+    let mut ledger = state.ledger.write().await;
+    let mut table = ledger.fx_rates.clone();
+    for r in req {
+        table.upsert(FxRate {
+            base: r.base,
+            quote: r.quote,
+            mid: r.mid,
+            spread_bps: r.spread_bps,
+            updated_at: chrono::Utc::now(),
+        })?;
+    }
+    ledger.fx_rates = table;
+    Ok(Json(ledger.fx_rates.rates.clone()))
+}
+
+pub async fn load_fx_rates_handler(
+    State(state): State<AppState>,
+    Json(req): Json<SaveLoadRequest>,
+) -> Result<Json<Vec<FxRate>>, LedgerError> {
+    let path = PathBuf::from(req.path);
+    let table = tokio::task::spawn_blocking(move || RateTable::load_from_file(&path))
+        .await
+        .map_err(|e| LedgerError::Persistence(e.to_string()))??;
+    let mut ledger = state.ledger.write().await;
+    ledger.fx_rates = table;
+    Ok(Json(ledger.fx_rates.rates.clone()))
+}
+
+
+// --- Persistence Handlers (Save / Load) ---
 
 pub async fn save_handler(
     State(state): State<AppState>,
     Json(req): Json<SaveLoadRequest>,
-) -> Result<(StatusCode, String), (StatusCode, String)> {
+) -> Result<(StatusCode, String), LedgerError> {
     // Serialize while holding a read lock to get a consistent snapshot, then write to disk in blocking thread.
     let ledger_snapshot = {
         let ledger = state.ledger.read().await;
-        serde_json::to_string_pretty(&*ledger).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        serde_json::to_string_pretty(&*ledger).map_err(|e| LedgerError::Persistence(e.to_string()))?
     };
 
     let path = PathBuf::from(req.path);
     let path_clone = path.clone();
-    let write_result = tokio::task::spawn_blocking(move || std::fs::write(&path, ledger_snapshot))
+    tokio::task::spawn_blocking(move || std::fs::write(&path, ledger_snapshot))
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| LedgerError::Persistence(e.to_string()))?
+        .map_err(|e| LedgerError::Persistence(e.to_string()))?;
 
     Ok((StatusCode::OK, format!("Saved ledger to {:?}", path_clone)))
 }
@@ -192,18 +1292,19 @@ pub async fn save_handler(
 pub async fn load_handler(
     State(state): State<AppState>,
     Json(req): Json<SaveLoadRequest>,
-) -> Result<(StatusCode, String), (StatusCode, String)> {
+) -> Result<(StatusCode, String), LedgerError> {
     let path = PathBuf::from(req.path);
     let path_clone = path.clone();
     // read + parse on blocking thread
     let loaded: Ledger = tokio::task::spawn_blocking(move || {
         let s = std::fs::read_to_string(&path)?;
-        let ledger: Ledger = serde_json::from_str(&s)?;
+        let mut ledger: Ledger = serde_json::from_str(&s)?;
+        ledger.after_load()?;
         Ok::<Ledger, Box<dyn std::error::Error + Send + Sync>>(ledger)
     })
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    .map_err(|e| LedgerError::Persistence(e.to_string()))?
+    .map_err(|e| LedgerError::validation(format!("Invalid ledger snapshot: {}", e)))?;
 
     // replace in-memory ledger
     let mut ledger_guard = state.ledger.write().await;
@@ -213,15 +1314,60 @@ pub async fn load_handler(
 }
 
 
-/// --- Report Handler ---
-pub async fn report_handler(State(state): State<AppState>) -> Json<HashMap<&'static str, String>> {
+// --- Report Handler ---
+pub async fn report_handler(State(state): State<AppState>) -> Result<Json<HashMap<&'static str, String>>, LedgerError> {
     let ledger = state.ledger.read().await;
     let mut map = HashMap::new();
-    let total = ledger.total_assets();
-    map.insert("total_assets", format!("{}", total));
+    let format = |totals: Vec<Money>| totals.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(", ");
+    map.insert("total_assets", format(ledger.total_by_type(AccountType::Asset)?));
+    map.insert("total_liabilities", format(ledger.total_by_type(AccountType::Liability)?));
+    map.insert("total_equity", format(ledger.total_by_type(AccountType::Equity)?));
+    map.insert("total_income", format(ledger.total_by_type(AccountType::Income)?));
+    map.insert("total_expenses", format(ledger.total_by_type(AccountType::Expense)?));
     if let Some(acc) = ledger.richest_account() {
         map.insert("richest_account", format!("{} ({})", acc.id, acc.owner));
         map.insert("richest_balance", format!("{}", acc.balance));
     }
-    Json(map)
+    Ok(Json(map))
+}
+
+pub async fn trial_balance_handler(
+    State(state): State<AppState>,
+    Query(q): Query<ReportQuery>,
+) -> Result<Response, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let report = ledger.trial_balance(q.from, q.to)?;
+    Ok(match q.format {
+        ReportFormat::Json => Json(report).into_response(),
+        ReportFormat::Csv => ([(header::CONTENT_TYPE, "text/csv")], report.to_csv()).into_response(),
+    })
+}
+
+pub async fn levy_remittance_handler(
+    State(state): State<AppState>,
+    Query(q): Query<ReportQuery>,
+) -> Result<Response, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let report = ledger.levy_remittance(q.from, q.to)?;
+    Ok(match q.format {
+        ReportFormat::Json => Json(report).into_response(),
+        ReportFormat::Csv => ([(header::CONTENT_TYPE, "text/csv")], report.to_csv()).into_response(),
+    })
+}
+
+pub async fn balance_sheet_handler(
+    State(state): State<AppState>,
+    Query(q): Query<BalanceSheetQuery>,
+) -> Result<Json<BalanceSheet>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    let as_of = q.as_of.unwrap_or_else(chrono::Utc::now);
+    Ok(Json(ledger.balance_sheet(as_of, q.base)?))
+}
+
+pub async fn income_statement_handler(
+    State(state): State<AppState>,
+    Query(q): Query<IncomeStatementQuery>,
+) -> Result<Json<IncomeStatement>, LedgerError> {
+    let ledger = state.ledger.read().await;
+    Ok(Json(ledger.income_statement(q.from, q.to, q.base)?))
 }
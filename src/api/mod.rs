@@ -1,3 +1,5 @@
 pub mod handlers;
 pub mod routes;
-pub mod dto;
\ No newline at end of file
+pub mod dto;
+pub mod error;
+pub mod auth;
@@ -1,5 +1,5 @@
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use tower_http::cors::{Any, CorsLayer};
@@ -8,8 +8,30 @@ use crate::state::AppState;
 
 use super::handlers::{
     create_account_handler, get_balance_handler, find_account_by_owner_handler,
+    create_group_account_handler, set_parent_handler, rollup_handler,
+    set_overdraft_handler, remove_overdraft_handler,
+    get_account_handler, place_lien_handler, list_liens_handler, get_lien_handler, amend_lien_handler,
+    lift_lien_handler, enforce_lien_handler,
+    freeze_account_handler, unfreeze_account_handler, close_account_handler, reopen_account_handler,
+    activate_account_handler, mark_dormant_handler, upgrade_tier_handler,
+    create_customer_handler, list_customers_handler, get_customer_handler, set_customer_kyc_status_handler,
+    attach_account_handler, list_customer_accounts_handler,
     deposit_handler, withdraw_handler, transfer_handler,
-    list_transactions_handler, save_handler, load_handler, report_handler,
+    set_mandate_handler, clear_mandate_handler, list_instructions_handler, get_instruction_handler,
+    approve_instruction_handler, reject_instruction_handler,
+    journal_handler, list_approvals_handler, get_approval_handler, approve_request_handler, reject_request_handler,
+    list_transactions_handler, reverse_transaction_handler, save_handler, load_handler, report_handler, trial_balance_handler,
+    balance_sheet_handler, income_statement_handler,
+    fx_transfer_handler, list_fx_rates_handler, update_fx_rates_handler, load_fx_rates_handler,
+    list_system_accounts_handler, set_system_account_handler,
+    get_settings_handler, update_settings_handler, settings_history_handler,
+    list_products_handler, upsert_product_handler, assign_product_handler,
+    get_interest_handler, set_interest_handler, clear_interest_handler, run_interest_handler,
+    list_fee_rules_handler, create_fee_rule_handler, delete_fee_rule_handler, quote_fee_handler,
+    list_levy_rules_handler, create_levy_rule_handler, delete_levy_rule_handler, levy_remittance_handler,
+    authorize_handler, capture_hold_handler, release_hold_handler, list_holds_handler,
+    create_standing_order_handler, list_standing_orders_handler, get_standing_order_handler,
+    pause_standing_order_handler, resume_standing_order_handler, cancel_standing_order_handler,
 };
 
 /// Build the full application router.
@@ -19,13 +41,82 @@ pub fn routes(state:AppState) -> Router {
     Router::new()
         // Accounts
         .route("/accounts", post(create_account_handler).get(find_account_by_owner_handler))
+        .route("/accounts/groups", post(create_group_account_handler))
+        .route("/accounts/:id", get(get_account_handler))
         .route("/accounts/:id/balance", get(get_balance_handler))
+        .route("/accounts/:id/parent", put(set_parent_handler))
+        .route("/accounts/:id/rollup", get(rollup_handler))
+        .route("/accounts/:id/holds", get(list_holds_handler))
+        .route("/accounts/:id/overdraft", put(set_overdraft_handler).delete(remove_overdraft_handler))
+        .route("/accounts/:id/liens", post(place_lien_handler).get(list_liens_handler))
+        .route("/accounts/:id/liens/:lien_id", get(get_lien_handler).put(amend_lien_handler).delete(lift_lien_handler))
+        .route("/accounts/:id/liens/:lien_id/enforce", post(enforce_lien_handler))
+        .route("/accounts/:id/freeze", post(freeze_account_handler))
+        .route("/accounts/:id/unfreeze", post(unfreeze_account_handler))
+        .route("/accounts/:id/close", post(close_account_handler))
+        .route("/accounts/:id/reopen", post(reopen_account_handler))
+        .route("/accounts/:id/activate", post(activate_account_handler))
+        .route("/accounts/:id/dormant", post(mark_dormant_handler))
+        .route("/accounts/:id/kyc-tier", post(upgrade_tier_handler))
+        .route("/accounts/:id/mandate", put(set_mandate_handler).delete(clear_mandate_handler))
+        .route("/accounts/:id/product", put(assign_product_handler))
+        .route("/accounts/:id/interest", get(get_interest_handler).put(set_interest_handler).delete(clear_interest_handler))
+
+        // Customers
+        .route("/customers", post(create_customer_handler).get(list_customers_handler))
+        .route("/customers/:id", get(get_customer_handler))
+        .route("/customers/:id/kyc-status", put(set_customer_kyc_status_handler))
+        .route("/customers/:id/accounts", post(attach_account_handler).get(list_customer_accounts_handler))
 
         // Transactions
         .route("/deposit", post(deposit_handler))
         .route("/withdraw", post(withdraw_handler))
         .route("/transfer", post(transfer_handler))
         .route("/transactions", get(list_transactions_handler))
+        .route("/journals", post(journal_handler))
+        .route("/transactions/:id/reverse", post(reverse_transaction_handler))
+        .route("/fees/quote", post(quote_fee_handler))
+
+        // Maker-checker approvals
+        .route("/approvals", get(list_approvals_handler))
+        .route("/approvals/:id", get(get_approval_handler))
+        .route("/approvals/:id/approve", post(approve_request_handler))
+        .route("/approvals/:id/reject", post(reject_request_handler))
+
+        // Joint-account instructions
+        .route("/instructions", get(list_instructions_handler))
+        .route("/instructions/:id", get(get_instruction_handler))
+        .route("/instructions/:id/approve", post(approve_instruction_handler))
+        .route("/instructions/:id/reject", post(reject_instruction_handler))
+
+        // Authorization holds
+        .route("/holds", post(authorize_handler))
+        .route("/holds/:id/capture", post(capture_hold_handler))
+        .route("/holds/:id/release", post(release_hold_handler))
+
+        // Standing orders
+        .route("/standing-orders", post(create_standing_order_handler).get(list_standing_orders_handler))
+        .route("/standing-orders/:id", get(get_standing_order_handler))
+        .route("/standing-orders/:id/pause", post(pause_standing_order_handler))
+        .route("/standing-orders/:id/resume", post(resume_standing_order_handler))
+        .route("/standing-orders/:id/cancel", post(cancel_standing_order_handler))
+
+        // Foreign exchange
+        .route("/fx/transfer", post(fx_transfer_handler))
+        .route("/fx/rates", get(list_fx_rates_handler))
+        .route("/admin/fx/rates", put(update_fx_rates_handler))
+        .route("/admin/fx/rates/load", post(load_fx_rates_handler))
+
+        // Administration
+        .route("/admin/system-accounts", get(list_system_accounts_handler).put(set_system_account_handler))
+        .route("/admin/settings", get(get_settings_handler).put(update_settings_handler))
+        .route("/admin/settings/history", get(settings_history_handler))
+        .route("/admin/products", get(list_products_handler).put(upsert_product_handler))
+        .route("/admin/interest/run", post(run_interest_handler))
+        .route("/admin/fees", get(list_fee_rules_handler).post(create_fee_rule_handler))
+        .route("/admin/fees/:id", delete(delete_fee_rule_handler))
+        .route("/admin/levies", get(list_levy_rules_handler).post(create_levy_rule_handler))
+        .route("/admin/levies/:id", delete(delete_levy_rule_handler))
 
         // Persistence
         .route("/save", post(save_handler))
@@ -33,6 +124,10 @@ pub fn routes(state:AppState) -> Router {
 
         // Reports
         .route("/report", get(report_handler))
+        .route("/reports/trial-balance", get(trial_balance_handler))
+        .route("/reports/balance-sheet", get(balance_sheet_handler))
+        .route("/reports/income-statement", get(income_statement_handler))
+        .route("/reports/levy-remittance", get(levy_remittance_handler))
 
         // Add state here
         .with_state(state)
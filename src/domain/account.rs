@@ -1,13 +1,56 @@
-use crate::domain::currency::Currency;
+use std::fmt;
 
-pub type Kobo = i64;
+use crate::domain::{currency::Currency, error::LedgerError, interest::InterestTerms, kyc::{KycTier, TierChange}, lien::Lien, mandate::Mandate, money::{money_or_legacy_minor, Money}, transaction::Side};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Classification of an account in the chart of accounts.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum AccountType {
+    Asset,
+    /// Customer deposits are liabilities of the bank.
+    #[default]
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+impl AccountType {
+    pub const ALL: [AccountType; 5] = [
+        AccountType::Asset,
+        AccountType::Liability,
+        AccountType::Equity,
+        AccountType::Income,
+        AccountType::Expense,
+    ];
+
+    /// Side on which the account's balance grows.
+    pub fn normal_balance(&self) -> Side {
+        match self {
+            AccountType::Asset | AccountType::Expense => Side::Debit,
+            AccountType::Liability | AccountType::Equity | AccountType::Income => Side::Credit,
+        }
+    }
+}
 
 #[derive(Debug, Clone,serde::Serialize,serde::Deserialize)]
 pub struct Account {
     pub id: u32,
     pub owner: String,
-    pub balance: Kobo,
-    pub closed: bool,
+    /// Balance on the account's normal side: a positive balance is a debit
+    /// balance for assets and expenses and a credit balance for the rest.
+    #[serde(deserialize_with = "money_or_legacy_minor")]
+    pub balance: Money,
+    #[serde(default)]
+    pub status: AccountStatus,
+    /// Status changes, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_history: Vec<StatusChange>,
+    /// Read from snapshots taken before `status` existed and folded into it
+    /// on load, see [`Ledger::after_load`](crate::domain::ledger::Ledger::after_load).
+    #[serde(rename = "closed", default, skip_serializing)]
+    pub legacy_closed: bool,
     pub currency: Currency,
 
     #[serde(rename = "bankName", default)]
@@ -18,4 +61,199 @@ pub struct Account {
 
     #[serde(rename = "accountNumber", default)]
     pub account_number: String,
-}
\ No newline at end of file
+
+    #[serde(rename = "accountType", default)]
+    pub account_type: AccountType,
+
+    /// Internal account owned by the bank itself (settlement, FX, ...).
+    #[serde(default)]
+    pub system: bool,
+
+    /// Parent node in the general-ledger tree.
+    #[serde(rename = "parentId", default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<u32>,
+
+    /// Group accounts only aggregate their children and cannot be posted to.
+    #[serde(default)]
+    pub group: bool,
+
+    /// Arranged overdraft in minor units: how far below zero the balance may go.
+    #[serde(rename = "overdraftLimit", default)]
+    pub overdraft_limit: i64,
+
+    /// Code of the product the account was opened under, see [`Product`](crate::domain::product::Product).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+
+    /// Interest terms for this account, overriding those of its product.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interest: Option<InterestTerms>,
+
+    /// Liens placed on the account, including lifted and enforced ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub liens: Vec<Lien>,
+
+    /// KYC tier, which sets the account's balance and transaction limits.
+    #[serde(default = "KycTier::grandfathered")]
+    pub kyc_tier: KycTier,
+
+    /// Tier upgrades, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kyc_history: Vec<TierChange>,
+
+    /// Customer holding the account, see [`Customer`](crate::domain::customer::Customer).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_id: Option<u64>,
+
+    /// Signatories of a joint account; debits wait for their approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mandate: Option<Mandate>,
+}
+
+impl Account {
+    /// Fails unless the account's status lets an entry on `side` post to it.
+    pub fn ensure_can_post(&self, side: Side) -> Result<(), LedgerError> {
+        match self.status {
+            AccountStatus::Closed => Err(LedgerError::Closed(self.id)),
+            status if status.allows(side) => Ok(()),
+            status => Err(LedgerError::Restricted { account_id: self.id, status: status.to_string(), side }),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.status == AccountStatus::Closed
+    }
+}
+
+/// Postings a frozen account refuses.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FreezeScope {
+    /// No debits (post-no-debit); credits still post.
+    Debit,
+    /// No credits; debits still post.
+    Credit,
+    /// Nothing posts.
+    Total,
+}
+
+/// Where an account is in its lifecycle. See [`AccountStatus::can_become`]
+/// for the allowed transitions.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "state")]
+pub enum AccountStatus {
+    /// Opened but not yet activated: it can be funded but not debited.
+    Pending,
+    #[default]
+    Active,
+    /// Inactive for a long time: credits post, debits wait for reactivation.
+    Dormant,
+    Frozen { scope: FreezeScope },
+    Closed,
+}
+
+impl AccountStatus {
+    /// Whether an entry on `side` may post to an account in this status.
+    pub fn allows(self, side: Side) -> bool {
+        match self {
+            AccountStatus::Active => true,
+            AccountStatus::Pending | AccountStatus::Dormant => side == Side::Credit,
+            AccountStatus::Frozen { scope: FreezeScope::Debit } => side == Side::Credit,
+            AccountStatus::Frozen { scope: FreezeScope::Credit } => side == Side::Debit,
+            AccountStatus::Frozen { scope: FreezeScope::Total } | AccountStatus::Closed => false,
+        }
+    }
+
+    /// Whether an account may move from this status to `to`. A frozen
+    /// account must be unfrozen before it can be closed, and a closed one is
+    /// reopened as active.
+    pub fn can_become(self, to: AccountStatus) -> bool {
+        use AccountStatus::*;
+        self != to
+            && matches!(
+                (self, to),
+                (Pending, Active | Closed)
+                    | (Active, Dormant | Frozen { .. } | Closed)
+                    | (Dormant, Active | Frozen { .. } | Closed)
+                    | (Frozen { .. }, Active | Frozen { .. })
+                    | (Closed, Active)
+            )
+    }
+}
+
+impl fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountStatus::Pending => write!(f, "pending"),
+            AccountStatus::Active => write!(f, "active"),
+            AccountStatus::Dormant => write!(f, "dormant"),
+            AccountStatus::Frozen { scope: FreezeScope::Debit } => write!(f, "frozen for debits"),
+            AccountStatus::Frozen { scope: FreezeScope::Credit } => write!(f, "frozen for credits"),
+            AccountStatus::Frozen { scope: FreezeScope::Total } => write!(f, "frozen"),
+            AccountStatus::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+/// One entry in an account's status history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusChange {
+    pub from: AccountStatus,
+    pub to: AccountStatus,
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Purpose of an internal bank account. The ledger keeps one account per
+/// role and currency.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SystemRole {
+    /// Cash held at the central bank; counterpart of deposits and withdrawals.
+    Settlement,
+    /// Currency position the bank takes on when converting customer funds.
+    FxPosition,
+    /// Spread earned on currency conversions.
+    FxIncome,
+    /// Equity account funding the opening balance of new accounts.
+    OpeningBalance,
+    /// Interest paid to customers when accrued interest is capitalized.
+    InterestExpense,
+    /// Fees charged to customers on transfers and withdrawals.
+    FeeIncome,
+    /// Levies collected and owed to the regulator until remitted.
+    EmtlPayable,
+    VatPayable,
+    WhtPayable,
+    /// Funds recovered from customer accounts by enforcing liens.
+    LienRecovery,
+}
+
+impl SystemRole {
+    pub fn account_type(&self) -> AccountType {
+        match self {
+            SystemRole::Settlement => AccountType::Asset,
+            SystemRole::FxPosition | SystemRole::OpeningBalance => AccountType::Equity,
+            SystemRole::FxIncome | SystemRole::FeeIncome => AccountType::Income,
+            SystemRole::InterestExpense => AccountType::Expense,
+            SystemRole::EmtlPayable
+            | SystemRole::VatPayable
+            | SystemRole::WhtPayable
+            | SystemRole::LienRecovery => AccountType::Liability,
+        }
+    }
+
+    /// Owner name given to accounts opened for this role.
+    pub fn owner(&self) -> &'static str {
+        match self {
+            SystemRole::Settlement => "BANK",
+            SystemRole::FxPosition => "FX POSITION",
+            SystemRole::FxIncome => "FX INCOME",
+            SystemRole::OpeningBalance => "OPENING BALANCE EQUITY",
+            SystemRole::InterestExpense => "INTEREST EXPENSE",
+            SystemRole::FeeIncome => "FEE INCOME",
+            SystemRole::EmtlPayable => "EMTL PAYABLE",
+            SystemRole::VatPayable => "VAT PAYABLE",
+            SystemRole::WhtPayable => "WHT PAYABLE",
+            SystemRole::LienRecovery => "LIEN RECOVERY",
+        }
+    }
+}
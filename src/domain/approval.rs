@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    currency::Currency,
+    error::LedgerError,
+    ledger::{Ledger, LedgerSettings},
+    mandate::{Operation, Submission},
+    money::Money,
+    transaction::{Approval, Approver, Side},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ApprovalStatus {
+    /// Waiting for a checker.
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// A high-value operation entered by one operator (the maker) and held until
+/// a different operator (the checker) approves or rejects it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRequest {
+    pub id: u64,
+    pub operation: Operation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Customer who gave the instruction, for debits on a joint account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory: Option<u64>,
+    pub maker: String,
+    pub made_at: DateTime<Utc>,
+    pub status: ApprovalStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    /// Transaction posted on approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<u64>,
+    /// Instruction raised on approval when the debited account is joint and
+    /// still needs its signatories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_id: Option<u64>,
+    /// Hold placed on approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold_id: Option<u64>,
+    /// Standing order set up on approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standing_order_id: Option<u64>,
+}
+
+impl Ledger {
+    /// Whether `operation` is above the approval threshold for its currency.
+    /// Currencies without a threshold never need approval.
+    pub fn requires_approval(&self, operation: &Operation) -> Result<bool, LedgerError> {
+        Ok(self
+            .operation_amounts(operation)?
+            .into_iter()
+            .any(|amount| self.approval_threshold_below(amount).is_some()))
+    }
+
+    /// Amounts `operation` moves, one per currency: for an FX transfer, the
+    /// amount debited, and for a journal or a reversal, its total debits.
+    /// A hold or standing order counts at its amount; the capture of a hold
+    /// a checker already approved counts at nothing.
+    pub fn operation_amounts(&self, operation: &Operation) -> Result<Vec<Money>, LedgerError> {
+        let entries = match operation {
+            Operation::Withdrawal { amount, .. }
+            | Operation::Transfer { amount, .. }
+            | Operation::FxTransfer { amount, .. }
+            | Operation::Hold { amount, .. }
+            | Operation::StandingOrder { amount, .. } => return Ok(vec![*amount]),
+            Operation::Capture { hold_id, amount } => {
+                let hold = self.holds.get(hold_id).ok_or(LedgerError::NotFound {
+                    entity: "hold",
+                    id: hold_id.to_string(),
+                })?;
+                return Ok(match hold.approval {
+                    Some(_) => Vec::new(),
+                    None => vec![amount.unwrap_or(hold.amount)],
+                });
+            }
+            Operation::LienEnforcement { account_id, lien_id } => return Ok(vec![self.lien(*account_id, *lien_id)?.amount]),
+            Operation::Journal { entries } => entries.clone(),
+            Operation::Reversal { tx_id, .. } => self.reversal_entries(*tx_id)?,
+            Operation::ApprovalThresholds { .. } => return Ok(Vec::new()),
+        };
+        let mut debits: BTreeMap<Currency, Money> = BTreeMap::new();
+        for e in entries.iter().filter(|e| e.side == Side::Debit) {
+            let total = debits.entry(e.amount.currency).or_insert(Money::zero(e.amount.currency));
+            *total = total.checked_add(e.amount)?;
+        }
+        Ok(debits.into_values().collect())
+    }
+
+    /// The threshold `amount` is above, if any.
+    fn approval_threshold_below(&self, amount: Money) -> Option<Money> {
+        self.settings
+            .approval_thresholds
+            .get(&amount.currency)
+            .filter(|&&threshold| amount.amount_minor > threshold)
+            .map(|&threshold| Money::new(threshold, amount.currency))
+    }
+
+    pub(crate) fn queue_for_approval(
+        &mut self,
+        operation: Operation,
+        description: Option<String>,
+        signatory: Option<u64>,
+        maker: &str,
+    ) -> Result<u64, LedgerError> {
+        let id = self.next_approval_request_id;
+        self.next_approval_request_id = id.checked_add(1).ok_or(LedgerError::Overflow("Approval request id"))?;
+        self.approval_requests.insert(id, ApprovalRequest {
+            id,
+            operation,
+            description,
+            signatory,
+            maker: maker.to_string(),
+            made_at: Utc::now(),
+            status: ApprovalStatus::Pending,
+            checker: None,
+            checked_at: None,
+            rejection_reason: None,
+            tx_id: None,
+            instruction_id: None,
+            hold_id: None,
+            standing_order_id: None,
+        });
+        Ok(id)
+    }
+
+    pub fn approval_request(&self, id: u64) -> Result<&ApprovalRequest, LedgerError> {
+        self.approval_requests.get(&id).ok_or(LedgerError::NotFound {
+            entity: "approval request",
+            id: id.to_string(),
+        })
+    }
+
+    /// The pending request `id`, after checking `checker` is allowed to decide it.
+    fn pending_request(&self, id: u64, checker: &str) -> Result<ApprovalRequest, LedgerError> {
+        if checker.is_empty() {
+            return Err(LedgerError::validation("The checking operator is required"));
+        }
+        let request = self.approval_request(id)?;
+        if request.status != ApprovalStatus::Pending {
+            return Err(LedgerError::validation(format!("Approval request {} is {:?}", id, request.status)));
+        }
+        if request.maker == checker {
+            return Err(LedgerError::validation(format!(
+                "Operator {} made approval request {} and cannot also check it", checker, id
+            )));
+        }
+        Ok(request.clone())
+    }
+
+    /// Approves request `id` as `checker` and posts the operation through the
+    /// usual checks, recording the checker on the transaction. If the
+    /// operation no longer passes them, e.g. the balance has since dropped,
+    /// the request stays pending. A debit on a joint account moves on to
+    /// its signatories as an instruction. Holds and standing orders are set
+    /// up with the checker's approval on them. New approval thresholds take
+    /// effect, recorded as a settings change.
+    pub fn approve_request(&mut self, id: u64, checker: &str) -> Result<&ApprovalRequest, LedgerError> {
+        let checker = checker.trim();
+        let request = self.pending_request(id, checker)?;
+        let now = Utc::now();
+        let approvals = vec![Approval { approver: Approver::Operator(checker.to_string()), at: now }];
+        let outcome = match request.operation {
+            Operation::ApprovalThresholds { thresholds } => {
+                let settings = LedgerSettings { approval_thresholds: thresholds, ..self.settings.clone() };
+                self.apply_settings(settings, &request.maker, Some(checker));
+                None
+            }
+            operation => Some(self.carry_out(operation, request.description, request.signatory, approvals)?),
+        };
+        let request = self.approval_requests.get_mut(&id).expect("request checked above");
+        request.status = ApprovalStatus::Approved;
+        request.checker = Some(checker.to_string());
+        request.checked_at = Some(now);
+        match outcome {
+            Some(Submission::Posted { tx_id }) => request.tx_id = Some(tx_id),
+            Some(Submission::Pending { instruction_id }) => request.instruction_id = Some(instruction_id),
+            Some(Submission::Held { hold_id }) => request.hold_id = Some(hold_id),
+            Some(Submission::Scheduled { standing_order_id }) => request.standing_order_id = Some(standing_order_id),
+            Some(Submission::AwaitingApproval { .. }) => unreachable!("carry_out does not queue for approval"),
+            None => {}
+        }
+        Ok(request)
+    }
+
+    pub fn reject_request(&mut self, id: u64, checker: &str, reason: &str) -> Result<&ApprovalRequest, LedgerError> {
+        let checker = checker.trim();
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(LedgerError::validation("A rejection reason is required"));
+        }
+        self.pending_request(id, checker)?;
+        let request = self.approval_requests.get_mut(&id).expect("request checked above");
+        request.status = ApprovalStatus::Rejected;
+        request.checker = Some(checker.to_string());
+        request.checked_at = Some(Utc::now());
+        request.rejection_reason = Some(reason.to_string());
+        Ok(request)
+    }
+}
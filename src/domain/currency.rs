@@ -1,9 +1,37 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Currency {
     NGN, // Nigerian Naira
     USD, // US Dollar
     EUR, // Euro
     GBP, // British Pound
-}
\ No newline at end of file
+}
+
+impl Currency {
+    /// Every supported currency, in declaration order.
+    pub const ALL: [Currency; 4] = [Currency::NGN, Currency::USD, Currency::EUR, Currency::GBP];
+
+    /// Number of decimal places of the minor unit (ISO 4217 exponent).
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Currency::NGN | Currency::USD | Currency::EUR | Currency::GBP => 2,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::NGN => "₦",
+            Currency::USD => "$",
+            Currency::EUR => "€",
+            Currency::GBP => "£",
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
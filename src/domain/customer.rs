@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::Account,
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+};
+
+/// Where the bank is in verifying a customer's identity.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum KycStatus {
+    #[default]
+    Pending,
+    Verified,
+    Rejected,
+}
+
+/// A person the bank holds accounts for. Accounts point at their customer
+/// through [`Account::customer_id`]; one customer may hold many accounts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Customer {
+    pub id: u64,
+    pub name: String,
+    /// Bank Verification Number, 11 digits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bvn: Option<String>,
+    /// National Identification Number, 11 digits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nin: Option<String>,
+    pub phone: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub kyc_status: KycStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Contact and identity details supplied when a customer is onboarded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomerDetails {
+    pub name: String,
+    #[serde(default)]
+    pub bvn: Option<String>,
+    #[serde(default)]
+    pub nin: Option<String>,
+    pub phone: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+fn is_identity_number(value: &str) -> bool {
+    value.len() == 11 && value.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_phone_number(value: &str) -> bool {
+    let digits = value.strip_prefix('+').unwrap_or(value);
+    (10..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+impl CustomerDetails {
+    /// Trims the details, dropping blank optional fields, and checks they are well-formed.
+    fn normalized(self) -> Result<CustomerDetails, LedgerError> {
+        let optional = |v: Option<String>| v.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let details = CustomerDetails {
+            name: self.name.trim().to_string(),
+            bvn: optional(self.bvn),
+            nin: optional(self.nin),
+            phone: self.phone.trim().to_string(),
+            email: optional(self.email),
+            address: optional(self.address),
+        };
+        if details.name.is_empty() {
+            return Err(LedgerError::validation("Customer name is required"));
+        }
+        if details.bvn.is_none() && details.nin.is_none() {
+            return Err(LedgerError::validation("A customer needs a BVN or a NIN"));
+        }
+        if details.bvn.as_deref().is_some_and(|v| !is_identity_number(v)) {
+            return Err(LedgerError::validation("BVN must be exactly 11 digits"));
+        }
+        if details.nin.as_deref().is_some_and(|v| !is_identity_number(v)) {
+            return Err(LedgerError::validation("NIN must be exactly 11 digits"));
+        }
+        if !is_phone_number(&details.phone) {
+            return Err(LedgerError::validation("Phone must be 10 to 15 digits with an optional leading +"));
+        }
+        if details.email.as_deref().is_some_and(|v| !v.contains('@')) {
+            return Err(LedgerError::validation("Email address is not valid"));
+        }
+        Ok(details)
+    }
+}
+
+impl Ledger {
+    /// Onboards a customer. BVN and NIN identify a single person, so each
+    /// may belong to one customer only.
+    pub fn create_customer(&mut self, details: CustomerDetails) -> Result<u64, LedgerError> {
+        let details = details.normalized()?;
+        for customer in self.customers.values() {
+            let taken = |mine: &Option<String>, theirs: &Option<String>| mine.is_some() && mine == theirs;
+            if taken(&details.bvn, &customer.bvn) {
+                return Err(LedgerError::validation(format!("BVN is already registered to customer {}", customer.id)));
+            }
+            if taken(&details.nin, &customer.nin) {
+                return Err(LedgerError::validation(format!("NIN is already registered to customer {}", customer.id)));
+            }
+        }
+        let id = self.next_customer_id;
+        self.next_customer_id = id.checked_add(1).ok_or(LedgerError::Overflow("Customer id"))?;
+        self.customers.insert(id, Customer {
+            id,
+            name: details.name,
+            bvn: details.bvn,
+            nin: details.nin,
+            phone: details.phone,
+            email: details.email,
+            address: details.address,
+            kyc_status: KycStatus::Pending,
+            created_at: Utc::now(),
+        });
+        Ok(id)
+    }
+
+    pub fn customer(&self, id: u64) -> Result<&Customer, LedgerError> {
+        self.customers.get(&id).ok_or(LedgerError::NotFound {
+            entity: "customer",
+            id: id.to_string(),
+        })
+    }
+
+    pub fn set_customer_kyc_status(&mut self, id: u64, status: KycStatus) -> Result<(), LedgerError> {
+        self.customer(id)?;
+        self.customers.get_mut(&id).expect("customer checked above").kyc_status = status;
+        Ok(())
+    }
+
+    /// Links a customer account to `customer_id`. An account belongs to at
+    /// most one customer; attaching it again to the same customer is a no-op.
+    pub fn attach_account(&mut self, customer_id: u64, account_id: u32) -> Result<(), LedgerError> {
+        self.customer(customer_id)?;
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and has no customer", account_id
+            )));
+        }
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        match acc.customer_id {
+            Some(existing) if existing != customer_id => Err(LedgerError::validation(format!(
+                "Account {} already belongs to customer {}", account_id, existing
+            ))),
+            _ if acc.mandate.as_ref().is_some_and(|m| !m.is_signatory(customer_id)) => {
+                Err(LedgerError::validation(format!(
+                    "Customer {} is not a signatory of joint account {}", customer_id, account_id
+                )))
+            }
+            _ => {
+                acc.customer_id = Some(customer_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Accounts `customer_id` holds or signs for, by id.
+    pub fn customer_accounts(&self, customer_id: u64) -> Result<Vec<&Account>, LedgerError> {
+        self.customer(customer_id)?;
+        let mut accounts: Vec<&Account> = self
+            .accounts
+            .values()
+            .filter(|acc| {
+                acc.customer_id == Some(customer_id) || acc.mandate.as_ref().is_some_and(|m| m.is_signatory(customer_id))
+            })
+            .collect();
+        accounts.sort_by_key(|acc| acc.id);
+        Ok(accounts)
+    }
+
+    /// Total balance of the customer's accounts, one total per currency.
+    pub fn customer_balances(&self, customer_id: u64) -> Result<Vec<Money>, LedgerError> {
+        let mut totals: BTreeMap<Currency, Money> = BTreeMap::new();
+        for acc in self.customer_accounts(customer_id)? {
+            let total = totals.entry(acc.currency).or_insert(Money::zero(acc.currency));
+            *total = total.checked_add(acc.balance)?;
+        }
+        Ok(totals.into_values().collect())
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use crate::domain::{currency::Currency, kyc::{KycTier, LimitKind}, transaction::Side};
+
+/// Errors returned by `Ledger` operations.
+///
+/// Every variant carries a stable, machine-readable [`code`](LedgerError::code)
+/// so API clients can branch on the failure without parsing the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerError {
+    /// A referenced entity (account, transaction, ...) does not exist.
+    NotFound { entity: &'static str, id: String },
+    /// The account is closed and cannot be posted to.
+    Closed(u32),
+    /// The account's status blocks postings on `side`, e.g. a debit freeze.
+    Restricted { account_id: u32, status: String, side: Side },
+    /// The account cannot cover the requested amount.
+    InsufficientFunds {
+        account_id: u32,
+        available: i64,
+        requested: i64,
+    },
+    /// The operation would take the account past a limit of its KYC tier.
+    LimitExceeded {
+        account_id: u32,
+        tier: KycTier,
+        limit: LimitKind,
+        allowed: i64,
+        attempted: i64,
+    },
+    /// Total debits and credits of a transaction differ within a currency.
+    Unbalanced {
+        currency: Currency,
+        debits: i64,
+        credits: i64,
+    },
+    /// Accounts of different currencies were mixed without a conversion.
+    CurrencyMismatch { expected: Currency, found: Currency },
+    /// An amount or identifier exceeded its numeric range.
+    Overflow(&'static str),
+    /// The account still holds funds and cannot be closed.
+    BalanceNotZero { account_id: u32, balance: i64 },
+    /// The request itself is malformed or violates a business rule.
+    Validation(String),
+    /// The ledger snapshot could not be read or written.
+    Persistence(String),
+    /// An idempotency key was reused for a different request.
+    IdempotencyKeyReused(String),
+    /// The caller's operator credentials are missing or not recognised.
+    Unauthorized(String),
+}
+
+impl LedgerError {
+    pub fn account_not_found(id: u32) -> Self {
+        LedgerError::NotFound {
+            entity: "account",
+            id: id.to_string(),
+        }
+    }
+
+    pub fn validation(msg: impl Into<String>) -> Self {
+        LedgerError::Validation(msg.into())
+    }
+
+    /// Stable machine-readable error code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LedgerError::NotFound { .. } => "not_found",
+            LedgerError::Closed(_) => "account_closed",
+            LedgerError::Restricted { .. } => "account_restricted",
+            LedgerError::InsufficientFunds { .. } => "insufficient_funds",
+            LedgerError::LimitExceeded { .. } => "limit_exceeded",
+            LedgerError::Unbalanced { .. } => "unbalanced_transaction",
+            LedgerError::CurrencyMismatch { .. } => "currency_mismatch",
+            LedgerError::Overflow(_) => "overflow",
+            LedgerError::BalanceNotZero { .. } => "balance_not_zero",
+            LedgerError::Validation(_) => "validation_error",
+            LedgerError::Persistence(_) => "persistence_error",
+            LedgerError::IdempotencyKeyReused(_) => "idempotency_key_reused",
+            LedgerError::Unauthorized(_) => "unauthorized",
+        }
+    }
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerError::NotFound { entity, id } => write!(f, "{} {} not found", entity, id),
+            LedgerError::Closed(id) => write!(f, "Account {} is closed", id),
+            LedgerError::Restricted { account_id, status, side } => {
+                let posting = match side {
+                    Side::Debit => "debited",
+                    Side::Credit => "credited",
+                };
+                write!(f, "Account {} is {} and cannot be {}", account_id, status, posting)
+            }
+            LedgerError::InsufficientFunds {
+                account_id,
+                available,
+                requested,
+            } => write!(
+                f,
+                "Insufficient funds in account {}: available {}, requested {}",
+                account_id, available, requested
+            ),
+            LedgerError::LimitExceeded {
+                account_id,
+                tier,
+                limit,
+                allowed,
+                attempted,
+            } => write!(
+                f,
+                "Account {} would exceed its {} {} limit: allowed {}, attempted {}",
+                account_id, tier, limit, allowed, attempted
+            ),
+            LedgerError::Unbalanced {
+                currency,
+                debits,
+                credits,
+            } => write!(
+                f,
+                "Unbalanced transaction in {} debit:{} credit:{}",
+                currency, debits, credits
+            ),
+            LedgerError::CurrencyMismatch { expected, found } => write!(
+                f,
+                "Currency mismatch: expected {}, found {}",
+                expected, found
+            ),
+            LedgerError::Overflow(what) => write!(f, "{} overflow", what),
+            LedgerError::BalanceNotZero {
+                account_id,
+                balance,
+            } => write!(
+                f,
+                "Cannot close account {}: balance not zero ({})",
+                account_id, balance
+            ),
+            LedgerError::Validation(msg) => write!(f, "{}", msg),
+            LedgerError::Persistence(msg) => write!(f, "{}", msg),
+            LedgerError::IdempotencyKeyReused(key) => write!(
+                f,
+                "Idempotency key '{}' was already used for a different request",
+                key
+            ),
+            LedgerError::Unauthorized(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LedgerError {}
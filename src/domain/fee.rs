@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::SystemRole,
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    levy::LevyKind,
+    money::Money,
+    transaction::{Charge, ChargeKind, Side, TransactionEntry},
+};
+
+/// Customer operations a fee can be charged on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FeeOperation {
+    Transfer,
+    Withdrawal,
+}
+
+/// One amount band of a tiered schedule.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Largest amount, in minor units, the band covers; `None` for the last band.
+    #[serde(default)]
+    pub up_to: Option<i64>,
+    /// Fixed part of the fee in minor units.
+    #[serde(default)]
+    pub flat: i64,
+    /// Proportional part of the fee in basis points of the amount.
+    #[serde(default)]
+    pub bps: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum FeeSchedule {
+    /// The same fee whatever the amount, in minor units.
+    Flat { amount: i64 },
+    /// A share of the amount in basis points.
+    Percentage { bps: u32 },
+    /// Flat and percentage parts picked by the band the amount falls in.
+    /// Bands are in ascending order and only the last may be open-ended.
+    Tiered { tiers: Vec<FeeTier> },
+}
+
+/// How much an operation costs, for accounts of one product in one currency.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeRule {
+    pub id: u64,
+    pub operation: FeeOperation,
+    /// Product the rule applies to. A rule without a product applies to
+    /// accounts that no product-specific rule covers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    pub currency: Currency,
+    pub schedule: FeeSchedule,
+    /// Lowest fee charged, in minor units.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    /// Highest fee charged, in minor units.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cap: Option<i64>,
+}
+
+/// The fee an operation would incur if it were posted now.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeQuote {
+    pub operation: FeeOperation,
+    pub account_id: u32,
+    pub amount: Money,
+    pub fee: Money,
+    /// Amount plus fee: what leaves the account.
+    pub total: Money,
+    /// Rule the fee comes from, if any applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<u64>,
+}
+
+/// Highest rate a fee or levy may charge: the whole amount.
+pub(crate) const MAX_BPS: u32 = 10_000;
+
+/// `amount` times `bps` basis points, rounded half up.
+pub(crate) fn share(amount: i64, bps: u32) -> Result<i64, LedgerError> {
+    let scaled = (amount as i128 * bps as i128 + 5_000) / 10_000;
+    i64::try_from(scaled).map_err(|_| LedgerError::Overflow("Fee"))
+}
+
+impl FeeRule {
+    fn validate(&self) -> Result<(), LedgerError> {
+        let negative = |v: i64| v < 0;
+        let too_high = || LedgerError::validation(format!("Fee rate must not exceed {} bps", MAX_BPS));
+        match &self.schedule {
+            FeeSchedule::Flat { amount } if negative(*amount) => {
+                return Err(LedgerError::validation("Flat fee cannot be negative"));
+            }
+            FeeSchedule::Percentage { bps } if *bps > MAX_BPS => {
+                return Err(too_high());
+            }
+            FeeSchedule::Tiered { tiers } => {
+                if tiers.is_empty() {
+                    return Err(LedgerError::validation("Tiered fee needs at least one band"));
+                }
+                let mut previous = None;
+                for (i, tier) in tiers.iter().enumerate() {
+                    if negative(tier.flat) {
+                        return Err(LedgerError::validation("Flat fee cannot be negative"));
+                    }
+                    if tier.bps > MAX_BPS {
+                        return Err(too_high());
+                    }
+                    match tier.up_to {
+                        None if i + 1 < tiers.len() => {
+                            return Err(LedgerError::validation("Only the last fee band may be open-ended"));
+                        }
+                        Some(up_to) if previous.is_some_and(|p| up_to <= p) => {
+                            return Err(LedgerError::validation("Fee bands must be in ascending order"));
+                        }
+                        _ => previous = tier.up_to,
+                    }
+                }
+            }
+            _ => {}
+        }
+        if self.min.is_some_and(negative) || self.cap.is_some_and(negative) {
+            return Err(LedgerError::validation("Fee minimum and cap cannot be negative"));
+        }
+        if let (Some(min), Some(cap)) = (self.min, self.cap)
+            && min > cap
+        {
+            return Err(LedgerError::validation("Fee minimum exceeds the cap"));
+        }
+        Ok(())
+    }
+
+    /// Fee for moving `amount` minor units.
+    pub fn fee_for(&self, amount: i64) -> Result<i64, LedgerError> {
+        let fee = match &self.schedule {
+            FeeSchedule::Flat { amount } => *amount,
+            FeeSchedule::Percentage { bps } => share(amount, *bps)?,
+            FeeSchedule::Tiered { tiers } => {
+                let tier = tiers
+                    .iter()
+                    .find(|t| t.up_to.is_none_or(|up_to| amount <= up_to))
+                    .ok_or_else(|| LedgerError::validation(format!(
+                        "No fee band of rule {} covers an amount of {}", self.id, amount
+                    )))?;
+                tier.flat.checked_add(share(amount, tier.bps)?).ok_or(LedgerError::Overflow("Fee"))?
+            }
+        };
+        let fee = self.min.map_or(fee, |min| fee.max(min));
+        Ok(self.cap.map_or(fee, |cap| fee.min(cap)))
+    }
+}
+
+impl Ledger {
+    pub fn add_fee_rule(
+        &mut self,
+        operation: FeeOperation,
+        product: Option<String>,
+        currency: Currency,
+        schedule: FeeSchedule,
+        min: Option<i64>,
+        cap: Option<i64>,
+    ) -> Result<u64, LedgerError> {
+        if let Some(code) = &product {
+            self.product(code)?;
+        }
+        let rule = FeeRule { id: self.next_fee_rule_id, operation, product, currency, schedule, min, cap };
+        rule.validate()?;
+        if let Some(existing) = self.fee_rules.values().find(|r| {
+            r.operation == rule.operation && r.product == rule.product && r.currency == rule.currency
+        }) {
+            return Err(LedgerError::validation(format!(
+                "Fee rule {} already covers this operation, product and currency", existing.id
+            )));
+        }
+        let id = rule.id;
+        self.next_fee_rule_id = id.checked_add(1).ok_or(LedgerError::Overflow("Fee rule id"))?;
+        self.fee_rules.insert(id, rule);
+        Ok(id)
+    }
+
+    pub fn remove_fee_rule(&mut self, id: u64) -> Result<FeeRule, LedgerError> {
+        self.fee_rules.remove(&id).ok_or(LedgerError::NotFound {
+            entity: "fee rule",
+            id: id.to_string(),
+        })
+    }
+
+    /// Rule that prices `operation` for `account_id`: the one for its product,
+    /// else the product-less one for its currency.
+    pub fn fee_rule_for(&self, operation: FeeOperation, account_id: u32) -> Result<Option<&FeeRule>, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.system {
+            return Ok(None);
+        }
+        let matching = |product: Option<&String>| {
+            self.fee_rules.values().find(|r| {
+                r.operation == operation && r.currency == acc.currency && r.product.as_ref() == product
+            })
+        };
+        Ok(acc.product.as_ref().and_then(|p| matching(Some(p))).or_else(|| matching(None)))
+    }
+
+    pub fn quote_fee(&self, operation: FeeOperation, account_id: u32, amount: Money) -> Result<FeeQuote, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Amount must be positive"));
+        }
+        self.check_currency(account_id, amount)?;
+        let rule = self.fee_rule_for(operation, account_id)?;
+        let fee = match rule {
+            Some(rule) => Money::new(rule.fee_for(amount.amount_minor)?, amount.currency),
+            None => Money::zero(amount.currency),
+        };
+        Ok(FeeQuote {
+            operation,
+            account_id,
+            amount,
+            fee,
+            total: amount.checked_add(fee)?,
+            rule_id: rule.map(|r| r.id),
+        })
+    }
+
+    /// Adds the fee for `operation` to `entries`: the payer's debit grows by
+    /// the fee and a leg crediting the fee-income account is appended, followed
+    /// by VAT on the fee. Returns the charges to record on the transaction.
+    pub(crate) fn apply_fee(
+        &mut self,
+        operation: FeeOperation,
+        payer: u32,
+        amount: Money,
+        entries: &mut Vec<TransactionEntry>,
+    ) -> Result<Vec<Charge>, LedgerError> {
+        let quote = self.quote_fee(operation, payer, amount)?;
+        if quote.fee.is_zero() {
+            return Ok(Vec::new());
+        }
+        let income = self.ensure_system_account(SystemRole::FeeIncome, amount.currency)?;
+        let debit = entries
+            .iter_mut()
+            .find(|e| e.account_id == payer && e.side == Side::Debit)
+            .expect("payer has a debit leg");
+        debit.amount = debit.amount.checked_add(quote.fee)?;
+        entries.push(TransactionEntry::credit(income, quote.fee));
+        let mut charges = vec![Charge { kind: ChargeKind::Fee, payer, credit_account: income, amount: quote.fee }];
+        let today = chrono::Utc::now().date_naive();
+        charges.extend(self.apply_levy(LevyKind::Vat, payer, quote.fee, today, entries)?);
+        Ok(charges)
+    }
+}
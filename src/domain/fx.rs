@@ -0,0 +1,285 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::SystemRole,
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    transaction::TransactionEntry,
+};
+
+/// Quoted rate for one currency pair: how many units of `quote` one unit of
+/// `base` is worth, with the spread the bank charges around the mid rate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FxRate {
+    pub base: Currency,
+    pub quote: Currency,
+    pub mid: f64,
+    /// Full buy/sell spread in basis points; half of it is applied on each side.
+    #[serde(default)]
+    pub spread_bps: u32,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Rates are applied to amounts in parts per billion, so conversions are
+/// exact integer arithmetic however large the amount.
+const RATE_SCALE: i128 = 1_000_000_000;
+
+/// Full spread in basis points is split evenly between buy and sell, hence
+/// twice the usual 10,000 in the denominator.
+const HALF_SPREAD_SCALE: i128 = 20_000;
+
+impl FxRate {
+    /// Rate at which the bank buys `base` from a customer.
+    pub fn buy(&self) -> f64 {
+        self.mid * (1.0 - self.half_spread())
+    }
+
+    /// Rate at which the bank sells `base` to a customer.
+    pub fn sell(&self) -> f64 {
+        self.mid * (1.0 + self.half_spread())
+    }
+
+    fn half_spread(&self) -> f64 {
+        self.spread_bps as f64 / 20_000.0
+    }
+
+    /// Mid rate in parts per billion.
+    fn mid_units(&self) -> i128 {
+        (self.mid * RATE_SCALE as f64).round() as i128
+    }
+
+    fn validate(&self) -> Result<(), LedgerError> {
+        if self.base == self.quote {
+            return Err(LedgerError::validation("FX rate base and quote currencies must differ"));
+        }
+        if !self.mid.is_finite() || self.mid <= 0.0 {
+            return Err(LedgerError::validation("FX mid rate must be a positive number"));
+        }
+        if self.mid_units() < 1 || self.mid_units() > i64::MAX as i128 {
+            return Err(LedgerError::validation("FX mid rate must be between 0.000000001 and 9223372036"));
+        }
+        if self.spread_bps >= 10_000 {
+            return Err(LedgerError::validation("FX spread must be below 10000 bps"));
+        }
+        Ok(())
+    }
+}
+
+/// An exact rate, as a fraction, in major units of the target currency per
+/// major unit of the source currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateRatio {
+    pub numerator: i128,
+    pub denominator: i128,
+}
+
+impl RateRatio {
+    /// The rate as a float, for display and for recording on transactions.
+    pub fn as_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+/// Rate to apply when converting from one currency into another, expressed as
+/// units of the target currency per unit of the source currency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FxQuote {
+    pub mid: RateRatio,
+    pub applied: RateRatio,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateTable {
+    pub rates: Vec<FxRate>,
+}
+
+impl RateTable {
+    pub fn load_from_file(path: &std::path::Path) -> Result<Self, LedgerError> {
+        let s = std::fs::read_to_string(path).map_err(|e| LedgerError::Persistence(e.to_string()))?;
+        let table: RateTable = serde_json::from_str(&s)
+            .map_err(|e| LedgerError::validation(format!("Invalid FX rate file: {}", e)))?;
+        for rate in &table.rates {
+            rate.validate()?;
+        }
+        Ok(table)
+    }
+
+    /// Inserts or replaces the rate for `rate.base`/`rate.quote`. A rate
+    /// quoted in the opposite direction is replaced as well.
+    pub fn upsert(&mut self, rate: FxRate) -> Result<(), LedgerError> {
+        rate.validate()?;
+        self.rates.retain(|r| {
+            !((r.base == rate.base && r.quote == rate.quote) || (r.base == rate.quote && r.quote == rate.base))
+        });
+        self.rates.push(rate);
+        Ok(())
+    }
+
+    /// Quote for converting `from` into `to`, using the inverse pair when
+    /// only the opposite direction is on the table.
+    pub fn quote(&self, from: Currency, to: Currency) -> Option<FxQuote> {
+        self.rates.iter().find_map(|r| {
+            let mid = r.mid_units();
+            let bps = r.spread_bps as i128;
+            if r.base == from && r.quote == to {
+                Some(FxQuote {
+                    mid: RateRatio { numerator: mid, denominator: RATE_SCALE },
+                    applied: RateRatio {
+                        numerator: mid * (HALF_SPREAD_SCALE - bps),
+                        denominator: RATE_SCALE * HALF_SPREAD_SCALE,
+                    },
+                })
+            } else if r.base == to && r.quote == from {
+                Some(FxQuote {
+                    mid: RateRatio { numerator: RATE_SCALE, denominator: mid },
+                    applied: RateRatio {
+                        numerator: RATE_SCALE * HALF_SPREAD_SCALE,
+                        denominator: mid * (HALF_SPREAD_SCALE + bps),
+                    },
+                })
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Conversion details recorded on an FX `Transaction`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FxConversion {
+    pub from: Currency,
+    pub to: Currency,
+    pub mid_rate: f64,
+    pub applied_rate: f64,
+    pub source_amount: Money,
+    pub target_amount: Money,
+    /// Spread earned by the bank, in the target currency.
+    pub spread_income: Money,
+}
+
+/// Converts `amount` into `to` at `rate` (major units of `to` per major unit
+/// of the source currency), rounding down to the target minor unit.
+fn convert(amount: Money, to: Currency, rate: RateRatio) -> Result<Money, LedgerError> {
+    let overflow = || LedgerError::Overflow("FX amount");
+    let exponent_shift = to.minor_units() as i32 - amount.currency.minor_units() as i32;
+    let scale = 10i128.pow(exponent_shift.unsigned_abs());
+    let (numerator, denominator) = if exponent_shift >= 0 {
+        (rate.numerator.checked_mul(scale).ok_or_else(overflow)?, rate.denominator)
+    } else {
+        (rate.numerator, rate.denominator.checked_mul(scale).ok_or_else(overflow)?)
+    };
+    let converted = (amount.amount_minor as i128)
+        .checked_mul(numerator)
+        .ok_or_else(overflow)?
+        .div_euclid(denominator);
+    Ok(Money::new(i64::try_from(converted).map_err(|_| overflow())?, to))
+}
+
+impl Ledger {
+    /// Works out the amounts for converting `amount` of `from` into `to`
+    /// without posting anything.
+    pub fn fx_quote(&self, amount: Money, to: Currency) -> Result<FxConversion, LedgerError> {
+        let from = amount.currency;
+        if from == to {
+            return Err(LedgerError::validation("FX conversion requires two different currencies"));
+        }
+        let quote = self.fx_rates.quote(from, to).ok_or_else(|| LedgerError::NotFound {
+            entity: "FX rate",
+            id: format!("{}/{}", from, to),
+        })?;
+        let target_amount = convert(amount, to, quote.applied)?;
+        let mid_amount = convert(amount, to, quote.mid)?;
+        if !target_amount.is_positive() {
+            return Err(LedgerError::validation("Amount is too small to convert"));
+        }
+        Ok(FxConversion {
+            from,
+            to,
+            mid_rate: quote.mid.as_f64(),
+            applied_rate: quote.applied.as_f64(),
+            source_amount: amount,
+            target_amount,
+            spread_income: Money::new((mid_amount.amount_minor - target_amount.amount_minor).max(0), to),
+        })
+    }
+
+    /// Values `amount` in `to` at the mid rate, for reporting. Negative
+    /// amounts convert symmetrically to positive ones.
+    pub fn value_at_mid(&self, amount: Money, to: Currency) -> Result<Money, LedgerError> {
+        if amount.currency == to {
+            return Ok(amount);
+        }
+        let quote = self.fx_rates.quote(amount.currency, to).ok_or_else(|| LedgerError::NotFound {
+            entity: "FX rate",
+            id: format!("{}/{}", amount.currency, to),
+        })?;
+        let magnitude = Money::new(amount.amount_minor.checked_abs().ok_or(LedgerError::Overflow("Amount"))?, amount.currency);
+        let converted = convert(magnitude, to, quote.mid)?;
+        Ok(if amount.is_negative() { Money::new(-converted.amount_minor, to) } else { converted })
+    }
+
+    /// Moves `amount` out of `from_id` in its currency and credits `to_id` in
+    /// its own currency at the customer rate. The conversion runs through the
+    /// per-currency FX position accounts and the spread is booked to FX income.
+    /// Joint accounts are debited through [`Ledger::submit`] instead.
+    pub fn fx_transfer(
+        &mut self,
+        from_id: u32,
+        to_id: u32,
+        amount: Money,
+        description: Option<String>,
+    ) -> Result<u64, LedgerError> {
+        self.ensure_sole_operated(from_id)?;
+        self.post_fx_transfer(from_id, to_id, amount, description)
+    }
+
+    pub(crate) fn post_fx_transfer(
+        &mut self,
+        from_id: u32,
+        to_id: u32,
+        amount: Money,
+        description: Option<String>,
+    ) -> Result<u64, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Transfer amount must be positive"));
+        }
+        if from_id == to_id {
+            return Err(LedgerError::validation("Cannot transfer to the same account"));
+        }
+        let from = self.accounts.get(&from_id).ok_or(LedgerError::account_not_found(from_id))?;
+        let from_currency = from.currency;
+        if from_currency != amount.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: from_currency, found: amount.currency });
+        }
+        let to_currency = self.accounts.get(&to_id).ok_or(LedgerError::account_not_found(to_id))?.currency;
+
+        let conversion = self.fx_quote(amount, to_currency)?;
+        let source_position = self.ensure_system_account(SystemRole::FxPosition, from_currency)?;
+        let target_position = self.ensure_system_account(SystemRole::FxPosition, to_currency)?;
+        let income = self.ensure_system_account(SystemRole::FxIncome, to_currency)?;
+
+        // Each customer leg is held to its own tier's limits in its own currency
+        let debit = TransactionEntry::debit(from_id, amount);
+        let credit = TransactionEntry::credit(to_id, conversion.target_amount);
+        self.check_tier_limits(amount, std::slice::from_ref(&debit))?;
+        self.check_tier_limits(conversion.target_amount, std::slice::from_ref(&credit))?;
+
+        let mut entries = vec![
+            debit,
+            TransactionEntry::credit(source_position, amount),
+            credit,
+            TransactionEntry::debit(
+                target_position,
+                conversion.target_amount.checked_add(conversion.spread_income)?,
+            ),
+        ];
+        if conversion.spread_income.is_positive() {
+            entries.push(TransactionEntry::credit(income, conversion.spread_income));
+        }
+        self.record_conversion(description, entries, conversion)
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::domain::{
+    account::{Account, AccountStatus, AccountType},
+    currency::Currency,
+    error::LedgerError,
+    kyc::KycTier,
+    ledger::Ledger,
+    money::Money,
+};
+
+/// Aggregated balances of an account and everything below it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RollupNode {
+    pub account_id: u32,
+    pub owner: String,
+    pub account_type: AccountType,
+    pub group: bool,
+    /// Sum of the leaf balances in this subtree, one entry per currency.
+    pub balances: Vec<Money>,
+    pub children: Vec<RollupNode>,
+}
+
+impl Ledger {
+    /// Opens a group account: a non-posting node of the general-ledger tree
+    /// that only aggregates the balances of its children. Its currency is
+    /// nominal; roll-ups report each currency found below it separately.
+    pub fn create_group_account(
+        &mut self,
+        name: String,
+        account_type: AccountType,
+        currency: Currency,
+        parent_id: Option<u32>,
+    ) -> Result<u32, LedgerError> {
+        if let Some(parent_id) = parent_id {
+            self.validate_parent(parent_id, account_type)?;
+        }
+        let id = self.next_account_id;
+        let next_id = id.checked_add(1).ok_or(LedgerError::Overflow("Account id"))?;
+        let account = Account {
+            id,
+            owner: name,
+            balance: Money::zero(currency),
+            status: AccountStatus::Active,
+            status_history: Vec::new(),
+            legacy_closed: false,
+            currency,
+            bank_name: String::new(),
+            bank_code: String::new(),
+            account_number: format!("{:011}", id),
+            account_type,
+            system: true,
+            parent_id,
+            group: true,
+            overdraft_limit: 0,
+            product: None,
+            interest: None,
+            liens: Vec::new(),
+            kyc_tier: KycTier::Tier3,
+            kyc_history: Vec::new(),
+            customer_id: None,
+            mandate: None,
+        };
+        self.accounts.insert(id, account);
+        self.next_account_id = next_id;
+        Ok(id)
+    }
+
+    /// Checks that `parent_id` can hold a child of `account_type`: it must be
+    /// a group account of the same type.
+    pub fn validate_parent(&self, parent_id: u32, account_type: AccountType) -> Result<(), LedgerError> {
+        let parent = self
+            .accounts
+            .get(&parent_id)
+            .ok_or(LedgerError::account_not_found(parent_id))?;
+        if !parent.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is not a group account",
+                parent_id
+            )));
+        }
+        if parent.account_type != account_type {
+            return Err(LedgerError::validation(format!(
+                "Parent account {} is {:?}, expected {:?}",
+                parent_id, parent.account_type, account_type
+            )));
+        }
+        Ok(())
+    }
+
+    /// Moves `account_id` under `parent_id`, or to the top level when `None`.
+    pub fn set_parent(&mut self, account_id: u32, parent_id: Option<u32>) -> Result<(), LedgerError> {
+        let account_type = self
+            .accounts
+            .get(&account_id)
+            .ok_or(LedgerError::account_not_found(account_id))?
+            .account_type;
+        if let Some(parent_id) = parent_id {
+            self.validate_parent(parent_id, account_type)?;
+            // Walk up from the new parent to make sure we are not creating a cycle
+            let mut cursor = Some(parent_id);
+            while let Some(id) = cursor {
+                if id == account_id {
+                    return Err(LedgerError::validation(format!(
+                        "Account {} cannot be placed under its own descendant {}",
+                        account_id, parent_id
+                    )));
+                }
+                cursor = self.accounts.get(&id).and_then(|a| a.parent_id);
+            }
+        }
+        self.accounts
+            .get_mut(&account_id)
+            .expect("account checked above")
+            .parent_id = parent_id;
+        Ok(())
+    }
+
+    /// Aggregated balances for the subtree rooted at `account_id`, per currency.
+    pub fn rollup(&self, account_id: u32) -> Result<RollupNode, LedgerError> {
+        if !self.accounts.contains_key(&account_id) {
+            return Err(LedgerError::account_not_found(account_id));
+        }
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for acc in self.accounts.values() {
+            if let Some(parent_id) = acc.parent_id {
+                children.entry(parent_id).or_default().push(acc.id);
+            }
+        }
+        for ids in children.values_mut() {
+            ids.sort_unstable();
+        }
+        self.rollup_node(account_id, &children)
+    }
+
+    fn rollup_node(&self, account_id: u32, children: &HashMap<u32, Vec<u32>>) -> Result<RollupNode, LedgerError> {
+        let acc = &self.accounts[&account_id];
+        let mut totals: BTreeMap<Currency, Money> = BTreeMap::new();
+        if !acc.group {
+            totals.insert(acc.currency, acc.balance);
+        }
+        let mut nodes = Vec::new();
+        for child_id in children.get(&account_id).into_iter().flatten() {
+            let node = self.rollup_node(*child_id, children)?;
+            for balance in &node.balances {
+                let total = totals.entry(balance.currency).or_insert(Money::zero(balance.currency));
+                *total = total.checked_add(*balance)?;
+            }
+            nodes.push(node);
+        }
+        Ok(RollupNode {
+            account_id,
+            owner: acc.owner.clone(),
+            account_type: acc.account_type,
+            group: acc.group,
+            balances: totals.into_values().collect(),
+            children: nodes,
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{error::LedgerError, ledger::Ledger, money::Money};
+
+/// Balance of one account right after a transaction touched it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalancePoint {
+    pub tx_id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub balance: Money,
+}
+
+/// Per-account running balances in posting order, so point-in-time lookups
+/// are a binary search instead of a replay of the whole journal. Derived from
+/// `Ledger.transactions`; not persisted.
+#[derive(Debug, Clone, Default)]
+pub struct BalanceIndex {
+    points: HashMap<u32, Vec<BalancePoint>>,
+}
+
+impl BalanceIndex {
+    pub(crate) fn record(&mut self, account_id: u32, point: BalancePoint) {
+        self.points.entry(account_id).or_default().push(point);
+    }
+
+    /// Last recorded balance at or before `as_of`.
+    fn at(&self, account_id: u32, as_of: DateTime<Utc>) -> Option<Money> {
+        let points = self.points.get(&account_id)?;
+        let after = points.partition_point(|p| p.timestamp <= as_of);
+        after.checked_sub(1).map(|i| points[i].balance)
+    }
+
+    /// Balance after the last transaction that touched the account.
+    pub(crate) fn latest(&self, account_id: u32) -> Option<Money> {
+        self.points.get(&account_id)?.last().map(|p| p.balance)
+    }
+}
+
+impl Ledger {
+    /// Balance of `account_id` as it stood at `as_of`, including every
+    /// transaction timestamped at or before that instant.
+    pub fn balance_as_of(&self, account_id: u32, as_of: DateTime<Utc>) -> Result<Money, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        Ok(self
+            .balance_index
+            .at(account_id, as_of)
+            .unwrap_or(Money::zero(acc.currency)))
+    }
+
+    /// Recomputes the balance index from the journal. Needed after a
+    /// snapshot is deserialized.
+    pub fn rebuild_balance_index(&mut self) -> Result<(), LedgerError> {
+        let mut index = BalanceIndex::default();
+        let mut running: HashMap<u32, Money> = HashMap::new();
+        for tx in &self.transactions {
+            for e in &tx.entries {
+                let acc = self
+                    .accounts
+                    .get(&e.account_id)
+                    .ok_or(LedgerError::account_not_found(e.account_id))?;
+                let current = running.get(&e.account_id).copied().unwrap_or(Money::zero(acc.currency));
+                let updated = if e.side == acc.account_type.normal_balance() {
+                    current.checked_add(e.amount)?
+                } else {
+                    current.checked_sub(e.amount)?
+                };
+                running.insert(e.account_id, updated);
+                index.record(e.account_id, BalancePoint { tx_id: tx.id, timestamp: tx.timestamp, balance: updated });
+            }
+        }
+        self.balance_index = index;
+        Ok(())
+    }
+}
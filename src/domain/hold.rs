@@ -0,0 +1,240 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    transaction::{Approval, Side, TransactionEntry},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HoldStatus {
+    Active,
+    Captured,
+    Released,
+    Expired,
+}
+
+/// Funds reserved on an account by an authorization. A hold lowers the
+/// available balance without posting anything until it is captured.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hold {
+    pub id: u64,
+    pub account_id: u32,
+    pub amount: Money,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: HoldStatus,
+    /// Amount actually posted when the hold was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured: Option<Money>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_tx_id: Option<u64>,
+    /// Checker who approved the hold when it was above the approval
+    /// threshold. Its capture needs no further approval and records it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<Approval>,
+}
+
+impl Hold {
+    /// Whether the hold still reserves funds at `now`.
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.status == HoldStatus::Active && self.expires_at > now
+    }
+}
+
+impl Ledger {
+    /// Balance minus every active hold and lien on the account.
+    pub fn available_balance(&self, account_id: u32) -> Result<Money, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        acc.balance
+            .checked_sub(self.held_amount(account_id, acc.currency)?)?
+            .checked_sub(acc.liened_amount()?)
+    }
+
+    /// Total of the active holds on `account_id`.
+    pub(crate) fn held_amount(&self, account_id: u32, currency: Currency) -> Result<Money, LedgerError> {
+        let now = Utc::now();
+        self.holds
+            .values()
+            .filter(|h| h.account_id == account_id && h.is_active_at(now))
+            .try_fold(Money::zero(currency), |held, h| held.checked_add(h.amount))
+    }
+
+    /// Fails with `InsufficientFunds` unless `account_id` can spend `amount`
+    /// out of its available balance and overdraft limit.
+    fn ensure_available(&self, account_id: u32, amount: Money) -> Result<(), LedgerError> {
+        let available = self.spendable_balance(account_id)?;
+        if available.amount_minor < amount.amount_minor {
+            return Err(LedgerError::InsufficientFunds {
+                account_id,
+                available: available.amount_minor,
+                requested: amount.amount_minor,
+            });
+        }
+        Ok(())
+    }
+
+    /// Places a hold of `amount` on `account_id`, within its tier limits. It
+    /// lapses after `ttl`, or after the configured default when no `ttl` is
+    /// given. Holds above the approval threshold are submitted as
+    /// [`Operation::Hold`](crate::domain::mandate::Operation::Hold) for a checker.
+    pub fn authorize(
+        &mut self,
+        account_id: u32,
+        amount: Money,
+        description: Option<String>,
+        ttl: Option<Duration>,
+    ) -> Result<u64, LedgerError> {
+        let ttl = self.hold_lifetime(account_id, amount, ttl)?;
+        self.expire_holds(Utc::now());
+        self.ensure_available(account_id, amount)?;
+        self.check_tier_limits(amount, &[TransactionEntry::debit(account_id, amount)])?;
+
+        let id = self.next_hold_id;
+        let now = Utc::now();
+        let expires_at = now
+            .checked_add_signed(ttl)
+            .ok_or_else(|| LedgerError::validation("Hold expiry is too far in the future"))?;
+        self.next_hold_id = id.checked_add(1).ok_or(LedgerError::Overflow("Hold id"))?;
+        self.holds.insert(id, Hold {
+            id,
+            account_id,
+            amount,
+            description,
+            created_at: now,
+            expires_at,
+            status: HoldStatus::Active,
+            captured: None,
+            capture_tx_id: None,
+            approval: None,
+        });
+        Ok(id)
+    }
+
+    /// Checks a hold of `amount` can be placed on `account_id`, leaving the
+    /// balance aside, and returns how long it will last.
+    pub(crate) fn hold_lifetime(&self, account_id: u32, amount: Money, ttl: Option<Duration>) -> Result<Duration, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Hold amount must be positive"));
+        }
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        acc.ensure_can_post(Side::Debit)?;
+        if acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is a group account and cannot hold funds", account_id
+            )));
+        }
+        self.ensure_sole_operated(account_id)?;
+        if acc.currency != amount.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: acc.currency, found: amount.currency });
+        }
+        let ttl = match ttl {
+            Some(ttl) => ttl,
+            None => Duration::try_seconds(self.settings.hold_ttl_secs)
+                .ok_or_else(|| LedgerError::validation("hold_ttl_secs is out of range"))?,
+        };
+        if ttl <= Duration::zero() {
+            return Err(LedgerError::validation("Hold expiry must be in the future"));
+        }
+        Ok(ttl)
+    }
+
+    /// Posts the held funds out through the settlement account. `amount`
+    /// defaults to the full hold; a smaller amount is a partial capture and
+    /// the remainder is released with the hold.
+    pub fn capture(&mut self, hold_id: u64, amount: Option<Money>, description: Option<String>) -> Result<u64, LedgerError> {
+        self.expire_holds(Utc::now());
+        let amount = self.capture_amount(hold_id, amount)?;
+        let hold = &self.holds[&hold_id];
+        let account_id = hold.account_id;
+        let description = description.or_else(|| hold.description.clone());
+        let settlement_id = self.settlement_account(amount.currency)?;
+        let entries = vec![
+            TransactionEntry::debit(account_id, amount),
+            TransactionEntry::credit(settlement_id, amount),
+        ];
+        // Limits are checked again: other debits may have used up the day's allowance since authorization
+        self.check_tier_limits(amount, &entries)?;
+
+        // Retire the hold first so the floor check does not count it against its own capture
+        self.holds.get_mut(&hold_id).expect("hold checked above").status = HoldStatus::Captured;
+        let tx_id = match self.record_transaction(description, entries) {
+            Ok(tx_id) => tx_id,
+            Err(e) => {
+                self.holds.get_mut(&hold_id).expect("hold checked above").status = HoldStatus::Active;
+                return Err(e);
+            }
+        };
+        let hold = self.holds.get_mut(&hold_id).expect("hold checked above");
+        hold.captured = Some(amount);
+        hold.capture_tx_id = Some(tx_id);
+        if let Some(approval) = hold.approval.clone() {
+            self.transactions.last_mut().expect("transaction just posted").approvals = vec![approval];
+        }
+        Ok(tx_id)
+    }
+
+    /// The amount a capture of `hold_id` posts, `amount` or else the full
+    /// hold, after checking the hold covers it.
+    pub(crate) fn capture_amount(&self, hold_id: u64, amount: Option<Money>) -> Result<Money, LedgerError> {
+        let hold = self.active_hold(hold_id)?;
+        let amount = amount.unwrap_or(hold.amount);
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Capture amount must be positive"));
+        }
+        if amount.currency != hold.amount.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: hold.amount.currency, found: amount.currency });
+        }
+        if amount.amount_minor > hold.amount.amount_minor {
+            return Err(LedgerError::validation(format!(
+                "Capture of {} exceeds hold {} of {}",
+                amount, hold_id, hold.amount
+            )));
+        }
+        Ok(amount)
+    }
+
+    /// Frees the funds reserved by an active hold.
+    pub fn release(&mut self, hold_id: u64) -> Result<(), LedgerError> {
+        self.expire_holds(Utc::now());
+        self.active_hold(hold_id)?;
+        self.holds.get_mut(&hold_id).expect("hold checked above").status = HoldStatus::Released;
+        Ok(())
+    }
+
+    /// Marks every active hold past its expiry as expired. Returns how many
+    /// holds lapsed.
+    pub fn expire_holds(&mut self, now: DateTime<Utc>) -> usize {
+        let mut expired = 0;
+        for hold in self.holds.values_mut() {
+            if hold.status == HoldStatus::Active && hold.expires_at <= now {
+                hold.status = HoldStatus::Expired;
+                expired += 1;
+            }
+        }
+        expired
+    }
+
+    /// Holds placed on `account_id`, oldest first.
+    pub fn holds_for_account(&self, account_id: u32) -> Vec<&Hold> {
+        self.holds.values().filter(|h| h.account_id == account_id).collect()
+    }
+
+    fn active_hold(&self, hold_id: u64) -> Result<&Hold, LedgerError> {
+        let hold = self.holds.get(&hold_id).ok_or(LedgerError::NotFound {
+            entity: "hold",
+            id: hold_id.to_string(),
+        })?;
+        if hold.status != HoldStatus::Active {
+            return Err(LedgerError::validation(format!(
+                "Hold {} is {:?}",
+                hold_id, hold.status
+            )));
+        }
+        Ok(hold)
+    }
+}
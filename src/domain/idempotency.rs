@@ -0,0 +1,55 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{error::LedgerError, ledger::Ledger};
+
+/// Outcome of a request made with an `Idempotency-Key`, kept so a retry of the
+/// same request gets the original response instead of being applied twice.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdempotencyRecord {
+    /// Operation name plus the canonical JSON of the request body.
+    pub fingerprint: String,
+    pub response: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fingerprint identifying a request body for `operation`. Object keys are
+/// sorted, so field order in the client's JSON does not matter.
+pub fn fingerprint<T: Serialize>(operation: &str, request: &T) -> Result<String, LedgerError> {
+    let body = serde_json::to_value(request)
+        .map_err(|e| LedgerError::validation(format!("Unserializable request: {}", e)))?;
+    Ok(format!("{} {}", operation, body))
+}
+
+impl Ledger {
+    /// The stored response for `key`, if the same request was already
+    /// processed within the retention window. Reusing a key for a different
+    /// request is rejected.
+    pub fn idempotent_response(&mut self, key: &str, fingerprint: &str) -> Result<Option<serde_json::Value>, LedgerError> {
+        self.purge_idempotency_keys(Utc::now());
+        match self.idempotency_keys.get(key) {
+            Some(record) if record.fingerprint != fingerprint => {
+                Err(LedgerError::IdempotencyKeyReused(key.to_string()))
+            }
+            Some(record) => Ok(Some(record.response.clone())),
+            None => Ok(None),
+        }
+    }
+
+    /// Remembers the response produced for `key`.
+    pub fn remember_response(&mut self, key: String, fingerprint: String, response: serde_json::Value) {
+        self.idempotency_keys.insert(key, IdempotencyRecord {
+            fingerprint,
+            response,
+            created_at: Utc::now(),
+        });
+    }
+
+    /// Drops keys older than the configured retention window.
+    pub fn purge_idempotency_keys(&mut self, now: DateTime<Utc>) -> usize {
+        let cutoff = now - Duration::seconds(self.settings.idempotency_retention_secs);
+        let before = self.idempotency_keys.len();
+        self.idempotency_keys.retain(|_, record| record.created_at > cutoff);
+        before - self.idempotency_keys.len()
+    }
+}
@@ -0,0 +1,273 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::SystemRole,
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    levy::LevyKind,
+    money::Money,
+    transaction::{Side, TransactionEntry},
+};
+
+/// Accrued interest is tracked in millionths of a minor unit so that small
+/// daily amounts are not lost to rounding before capitalization.
+const MICROS_PER_MINOR: i64 = 1_000_000;
+
+/// Day-count convention used to turn an annual rate into a daily amount.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DayCount {
+    /// Actual days over a 365-day year.
+    #[default]
+    #[serde(rename = "ACT/365")]
+    Act365,
+    /// Every month counts as 30 days, the year as 360.
+    #[serde(rename = "30/360")]
+    Thirty360,
+}
+
+impl DayCount {
+    /// Days between `from` and `to` and the days in a year, under this convention.
+    pub fn days(self, from: NaiveDate, to: NaiveDate) -> (i64, i64) {
+        match self {
+            DayCount::Act365 => ((to - from).num_days(), 365),
+            DayCount::Thirty360 => {
+                let d1 = from.day().min(30) as i64;
+                let d2 = if d1 == 30 { to.day().min(30) } else { to.day() } as i64;
+                let days = 360 * (to.year() - from.year()) as i64
+                    + 30 * (to.month() as i64 - from.month() as i64)
+                    + (d2 - d1);
+                (days, 360)
+            }
+        }
+    }
+
+    /// Fraction of a year between `from` and `to`.
+    pub fn year_fraction(self, from: NaiveDate, to: NaiveDate) -> f64 {
+        let (days, year) = self.days(from, to);
+        days as f64 / year as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InterestTerms {
+    /// Annual rate in basis points, e.g. 450 for 4.5%.
+    pub annual_rate_bps: u32,
+    #[serde(default)]
+    pub day_count: DayCount,
+}
+
+impl InterestTerms {
+    pub fn validate(&self) -> Result<(), LedgerError> {
+        if self.annual_rate_bps > 10_000 {
+            return Err(LedgerError::validation("Interest rate must not exceed 10000 bps"));
+        }
+        Ok(())
+    }
+}
+
+/// Interest earned by an account but not yet paid into its balance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InterestAccrual {
+    pub account_id: u32,
+    pub currency: Currency,
+    /// Accrued amount in millionths of the minor unit.
+    pub accrued_micros: i64,
+    /// Last day interest has been accrued for.
+    pub accrued_through: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_capitalized: Option<NaiveDate>,
+}
+
+impl InterestAccrual {
+    /// Accrued interest in whole minor units.
+    pub fn accrued(&self) -> Money {
+        Money::new(self.accrued_micros / MICROS_PER_MINOR, self.currency)
+    }
+}
+
+/// Interest paid into an account at a month end.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Capitalization {
+    pub account_id: u32,
+    pub date: NaiveDate,
+    /// Gross interest, before withholding tax.
+    pub amount: Money,
+    /// Withholding tax deducted from `amount`.
+    pub withheld: Money,
+    pub tx_id: u64,
+}
+
+/// An account whose interest could not be accrued, and why.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AccrualFailure {
+    pub account_id: u32,
+    pub error: String,
+}
+
+/// Outcome of [`Ledger::accrue_interest`].
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AccrualRun {
+    pub capitalizations: Vec<Capitalization>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<AccrualFailure>,
+}
+
+/// Interest in millionths of a minor unit on `balance` at `annual_rate_bps`
+/// for `days` out of a `year`-day year, rounded half up.
+fn daily_interest_micros(balance: i64, annual_rate_bps: u32, days: i64, year: i64) -> Result<i64, LedgerError> {
+    let numerator = (balance as i128 * annual_rate_bps as i128)
+        .checked_mul(days as i128 * MICROS_PER_MINOR as i128)
+        .ok_or(LedgerError::Overflow("Accrued interest"))?;
+    let denominator = 10_000 * year as i128;
+    let interest = (numerator + denominator / 2).div_euclid(denominator);
+    i64::try_from(interest).map_err(|_| LedgerError::Overflow("Accrued interest"))
+}
+
+fn end_of_day(date: NaiveDate) -> DateTime<Utc> {
+    (date + Duration::days(1)).and_time(chrono::NaiveTime::MIN).and_utc() - Duration::nanoseconds(1)
+}
+
+fn is_month_end(date: NaiveDate) -> bool {
+    (date + Duration::days(1)).day() == 1
+}
+
+impl Ledger {
+    /// Interest terms in force for an account: its own, else its product's.
+    pub fn interest_terms(&self, account_id: u32) -> Result<Option<InterestTerms>, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.interest.is_some() {
+            return Ok(acc.interest);
+        }
+        Ok(acc
+            .product
+            .as_deref()
+            .and_then(|code| self.products.get(code))
+            .and_then(|product| product.interest))
+    }
+
+    /// Sets or clears the account-level interest override.
+    pub fn set_account_interest(&mut self, account_id: u32, terms: Option<InterestTerms>) -> Result<(), LedgerError> {
+        if let Some(terms) = &terms {
+            terms.validate()?;
+        }
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and cannot earn interest", account_id
+            )));
+        }
+        acc.interest = terms;
+        Ok(())
+    }
+
+    /// Accrues daily interest on every interest-bearing account up to and
+    /// including `through`, using each day's closing balance, and capitalizes
+    /// at every month end passed on the way. Days already accrued are not
+    /// accrued again, so re-running for the same date changes nothing. An
+    /// account seen for the first time starts accruing on `through`. An
+    /// account that fails keeps the days accrued before the failure, is
+    /// reported in the run and is picked up again by the next one.
+    pub fn accrue_interest(&mut self, through: NaiveDate) -> AccrualRun {
+        let mut ids: Vec<u32> = self
+            .accounts
+            .values()
+            .filter(|acc| !acc.is_closed() && !acc.system && !acc.group)
+            .map(|acc| acc.id)
+            .collect();
+        ids.sort_unstable();
+
+        let mut run = AccrualRun::default();
+        for account_id in ids {
+            if let Err(e) = self.accrue_account_interest(account_id, through, &mut run.capitalizations) {
+                run.failures.push(AccrualFailure { account_id, error: e.to_string() });
+            }
+        }
+        run
+    }
+
+    fn accrue_account_interest(
+        &mut self,
+        account_id: u32,
+        through: NaiveDate,
+        capitalizations: &mut Vec<Capitalization>,
+    ) -> Result<(), LedgerError> {
+        let Some(terms) = self.interest_terms(account_id)? else { return Ok(()) };
+        let currency = self.accounts[&account_id].currency;
+        let mut accrual = match self.interest_accruals.get(&account_id) {
+            Some(accrual) if accrual.accrued_through >= through => return Ok(()),
+            Some(accrual) => accrual.clone(),
+            None => InterestAccrual {
+                account_id,
+                currency,
+                accrued_micros: 0,
+                accrued_through: through - Duration::days(1),
+                last_capitalized: None,
+            },
+        };
+        let mut result = Ok(());
+        while accrual.accrued_through < through {
+            match self.accrue_day(&accrual, terms) {
+                Ok((next, capitalization)) => {
+                    accrual = next;
+                    capitalizations.extend(capitalization);
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.interest_accruals.insert(account_id, accrual);
+        result
+    }
+
+    /// `accrual` after one more day, capitalized if that day ends a month.
+    fn accrue_day(
+        &mut self,
+        accrual: &InterestAccrual,
+        terms: InterestTerms,
+    ) -> Result<(InterestAccrual, Option<Capitalization>), LedgerError> {
+        let mut next = accrual.clone();
+        let day = next.accrued_through + Duration::days(1);
+        let balance = self.balance_as_of(next.account_id, end_of_day(day))?;
+        if balance.is_positive() {
+            let (days, year) = terms.day_count.days(next.accrued_through, day);
+            let interest = daily_interest_micros(balance.amount_minor, terms.annual_rate_bps, days, year)?;
+            next.accrued_micros = next
+                .accrued_micros
+                .checked_add(interest)
+                .ok_or(LedgerError::Overflow("Accrued interest"))?;
+        }
+        next.accrued_through = day;
+        let capitalization = if is_month_end(day) { self.capitalize(&mut next, day)? } else { None };
+        Ok((next, capitalization))
+    }
+
+    /// Pays the whole minor units accrued so far into the account from the
+    /// interest-expense account, less withholding tax; the sub-unit remainder
+    /// keeps accruing.
+    fn capitalize(&mut self, accrual: &mut InterestAccrual, date: NaiveDate) -> Result<Option<Capitalization>, LedgerError> {
+        // An account that cannot take credits keeps accruing until it can
+        if self.accounts[&accrual.account_id].ensure_can_post(Side::Credit).is_err() {
+            return Ok(None);
+        }
+        let amount = accrual.accrued();
+        accrual.last_capitalized = Some(date);
+        if !amount.is_positive() {
+            return Ok(None);
+        }
+        let expense = self.ensure_system_account(SystemRole::InterestExpense, accrual.currency)?;
+        let mut entries = vec![
+            TransactionEntry::debit(expense, amount),
+            TransactionEntry::credit(accrual.account_id, amount),
+        ];
+        let wht = self.apply_levy(LevyKind::Wht, accrual.account_id, amount, date, &mut entries)?;
+        let withheld = wht.as_ref().map_or(Money::zero(amount.currency), |charge| charge.amount);
+        let description = format!("Interest capitalization {}", date.format("%Y-%m"));
+        let tx_id = self.record_charged_transaction(Some(description), entries, wht.into_iter().collect())?;
+        accrual.accrued_micros -= amount.amount_minor * MICROS_PER_MINOR;
+        Ok(Some(Capitalization { account_id: accrual.account_id, date, amount, withheld, tx_id }))
+    }
+}
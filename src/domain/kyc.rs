@@ -0,0 +1,213 @@
+use std::{collections::BTreeMap, fmt};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    transaction::TransactionEntry,
+};
+
+/// Customer due-diligence level of an account, after the CBN's three-tiered
+/// KYC framework. Higher tiers have verified more of the customer's identity
+/// and get higher limits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KycTier {
+    /// Name, photo and phone number only.
+    Tier1,
+    /// Identity verified against BVN or NIN.
+    Tier2,
+    /// Full KYC including proof of address.
+    Tier3,
+}
+
+impl KycTier {
+    /// Tier of accounts read from snapshots taken before tiers existed. They
+    /// get the highest tier, so their balances stay uncapped, but from then
+    /// on the tier 3 transaction and daily debit limits apply to them.
+    pub fn grandfathered() -> KycTier {
+        KycTier::Tier3
+    }
+}
+
+impl fmt::Display for KycTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KycTier::Tier1 => write!(f, "tier 1"),
+            KycTier::Tier2 => write!(f, "tier 2"),
+            KycTier::Tier3 => write!(f, "tier 3"),
+        }
+    }
+}
+
+/// Limits of one tier in naira minor units; `None` means unlimited. Accounts
+/// in other currencies are not limited by tier.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TierLimits {
+    #[serde(default)]
+    pub max_balance: Option<i64>,
+    /// Largest amount a single deposit, withdrawal or transfer may move.
+    #[serde(default)]
+    pub single_transaction: Option<i64>,
+    /// Most the account may be debited over any rolling 24 hours.
+    #[serde(default)]
+    pub daily_debit: Option<i64>,
+}
+
+/// Currency the tier limits are expressed in.
+pub const KYC_LIMIT_CURRENCY: Currency = Currency::NGN;
+
+/// CBN limits: ₦300,000 balance and ₦50,000 per transaction and day for
+/// tier 1, ₦500,000 and ₦100,000 / ₦200,000 for tier 2, and no balance cap
+/// for tier 3.
+pub fn default_tier_limits() -> BTreeMap<KycTier, TierLimits> {
+    let naira = |n: i64| Some(n * 100);
+    [
+        (KycTier::Tier1, TierLimits { max_balance: naira(300_000), single_transaction: naira(50_000), daily_debit: naira(50_000) }),
+        (KycTier::Tier2, TierLimits { max_balance: naira(500_000), single_transaction: naira(100_000), daily_debit: naira(200_000) }),
+        (KycTier::Tier3, TierLimits { max_balance: None, single_transaction: naira(5_000_000), daily_debit: naira(25_000_000) }),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// The tier limit an operation ran into.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LimitKind {
+    MaxBalance,
+    SingleTransaction,
+    DailyDebit,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::MaxBalance => write!(f, "maximum balance"),
+            LimitKind::SingleTransaction => write!(f, "single transaction"),
+            LimitKind::DailyDebit => write!(f, "daily debit"),
+        }
+    }
+}
+
+/// One entry in an account's tier history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TierChange {
+    pub from: KycTier,
+    pub to: KycTier,
+    /// Operator who made the change.
+    pub by: String,
+    pub at: DateTime<Utc>,
+}
+
+impl Ledger {
+    /// Limits that apply to `account_id`, or `None` for internal accounts and
+    /// accounts outside the limit currency.
+    pub fn tier_limits(&self, account_id: u32) -> Result<Option<TierLimits>, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.system || acc.group || acc.currency != KYC_LIMIT_CURRENCY {
+            return Ok(None);
+        }
+        Ok(self.settings.kyc_limits.get(&acc.kyc_tier).copied())
+    }
+
+    /// Total debited from `account_id` since `since`, leaving out transactions
+    /// that were reversed.
+    pub fn debits_since(&self, account_id: u32, since: DateTime<Utc>) -> Result<Money, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        let decrease = acc.account_type.normal_balance().opposite();
+        let start = self.transactions.partition_point(|tx| tx.timestamp <= since);
+        self.transactions[start..]
+            .iter()
+            .filter(|tx| tx.reversed_by.is_none())
+            .flat_map(|tx| &tx.entries)
+            .filter(|e| e.account_id == account_id && e.side == decrease)
+            .try_fold(Money::zero(acc.currency), |total, e| total.checked_add(e.amount))
+    }
+
+    /// Checks a customer operation moving `amount` against the tier limits of
+    /// every account in `entries`: the single-transaction limit on `amount`,
+    /// the balance cap on accounts the entries credit and the rolling 24-hour
+    /// debit limit, fees included, on accounts they debit.
+    pub(crate) fn check_tier_limits(&self, amount: Money, entries: &[TransactionEntry]) -> Result<(), LedgerError> {
+        let now = Utc::now();
+        for e in entries {
+            let Some(limits) = self.tier_limits(e.account_id)? else {
+                continue;
+            };
+            let acc = &self.accounts[&e.account_id];
+            let exceeded = |limit: LimitKind, allowed: i64, attempted: i64| LedgerError::LimitExceeded {
+                account_id: e.account_id,
+                tier: acc.kyc_tier,
+                limit,
+                allowed,
+                attempted,
+            };
+            if let Some(allowed) = limits.single_transaction
+                && amount.amount_minor > allowed
+            {
+                return Err(exceeded(LimitKind::SingleTransaction, allowed, amount.amount_minor));
+            }
+            if e.side == acc.account_type.normal_balance() {
+                self.check_balance_cap(e)?;
+            } else if let Some(allowed) = limits.daily_debit {
+                let attempted = self
+                    .debits_since(e.account_id, now - Duration::hours(24))?
+                    .checked_add(e.amount)?
+                    .amount_minor;
+                if attempted > allowed {
+                    return Err(exceeded(LimitKind::DailyDebit, allowed, attempted));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the credit `e` leaves its account within the balance cap
+    /// of its tier.
+    pub(crate) fn check_balance_cap(&self, e: &TransactionEntry) -> Result<(), LedgerError> {
+        let Some(allowed) = self.tier_limits(e.account_id)?.and_then(|limits| limits.max_balance) else {
+            return Ok(());
+        };
+        let acc = &self.accounts[&e.account_id];
+        let attempted = acc.balance.checked_add(e.amount)?.amount_minor;
+        if attempted > allowed {
+            return Err(LedgerError::LimitExceeded {
+                account_id: e.account_id,
+                tier: acc.kyc_tier,
+                limit: LimitKind::MaxBalance,
+                allowed,
+                attempted,
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves a customer account to a higher tier, recording `by` as the
+    /// operator who verified the extra KYC.
+    pub fn upgrade_kyc_tier(&mut self, account_id: u32, to: KycTier, by: &str) -> Result<(), LedgerError> {
+        let by = by.trim();
+        if by.is_empty() {
+            return Err(LedgerError::validation("The operator upgrading the account is required"));
+        }
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and has no KYC tier", account_id
+            )));
+        }
+        if to <= acc.kyc_tier {
+            return Err(LedgerError::validation(format!(
+                "Account {} is already at {}; it can only be upgraded", account_id, acc.kyc_tier
+            )));
+        }
+        acc.kyc_history.push(TierChange { from: acc.kyc_tier, to, by: by.to_string(), at: Utc::now() });
+        acc.kyc_tier = to;
+        Ok(())
+    }
+}
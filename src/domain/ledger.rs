@@ -1,13 +1,113 @@
-use crate::domain::{account::Kobo, currency::{self, Currency}};
+use crate::domain::{account::{AccountStatus, AccountType, SystemRole}, approval::ApprovalRequest, currency::Currency, customer::Customer, error::LedgerError, fee::{FeeOperation, FeeRule}, fx::{FxConversion, RateTable}, history::{BalanceIndex, BalancePoint}, hold::Hold, idempotency::IdempotencyRecord, interest::InterestAccrual, kyc::{default_tier_limits, KycTier, TierLimits}, levy::{default_levy_rules, LevyKind, LevyRule}, mandate::{Instruction, Operation}, money::Money, product::Product, standing_order::StandingOrder};
 
-use super::{account::Account, transaction::{Transaction, TransactionEntry}};
-use std::collections::HashMap;
+use super::{account::Account, transaction::{Charge, Side, Transaction, TransactionEntry}};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use serde::{Serialize,Deserialize};
-use rand::{rngs::OsRng, thread_rng, Rng};
+use rand::Rng;
+use chrono::{DateTime, Utc};
+
+/// Tunable limits and policies, persisted with the ledger snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LedgerSettings {
+    /// Upper bound on the number of entries a single transaction may carry.
+    pub max_entries_per_transaction: usize,
+    /// Lifetime of an authorization hold when the caller does not set one.
+    pub hold_ttl_secs: i64,
+    /// How long idempotency keys are remembered.
+    pub idempotency_retention_secs: i64,
+    /// Limits per KYC tier, see [`TierLimits`].
+    pub kyc_limits: BTreeMap<KycTier, TierLimits>,
+    /// How long a joint-account instruction waits for its signatories.
+    pub instruction_ttl_secs: i64,
+    /// Amounts, in minor units per currency, above which withdrawals,
+    /// transfers and manual journals need a second operator's approval.
+    pub approval_thresholds: BTreeMap<Currency, i64>,
+}
+
+/// A change to the ledger settings and the operators behind it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingsChange {
+    /// Operator who made the change.
+    pub made_by: String,
+    /// Operator who approved it, for a change to the approval thresholds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<String>,
+    pub at: DateTime<Utc>,
+    /// Settings in force after the change.
+    pub settings: LedgerSettings,
+}
+
+/// Optional settings for a customer account being opened, see
+/// [`Ledger::open_account`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningOptions {
+    /// Group account to file the account under.
+    pub parent_id: Option<u32>,
+    /// Open the account as pending until it is activated.
+    pub pending: bool,
+    /// Customer the account belongs to.
+    pub customer_id: Option<u64>,
+}
+
+impl Default for LedgerSettings {
+    fn default() -> Self {
+        LedgerSettings {
+            max_entries_per_transaction: 50,
+            hold_ttl_secs: 7 * 24 * 60 * 60,
+            idempotency_retention_secs: 24 * 60 * 60,
+            kyc_limits: default_tier_limits(),
+            instruction_ttl_secs: 3 * 24 * 60 * 60,
+            approval_thresholds: BTreeMap::from([(Currency::NGN, 100_000_000)]),
+        }
+    }
+}
+
+const MAX_RETENTION_SECS: i64 = 365 * 24 * 60 * 60;
+const MAX_HOLD_TTL_SECS: i64 = 365 * 24 * 60 * 60;
+const MAX_INSTRUCTION_TTL_SECS: i64 = 365 * 24 * 60 * 60;
+
+impl LedgerSettings {
+    pub fn validate(&self) -> Result<(), LedgerError> {
+        if self.max_entries_per_transaction < 2 {
+            return Err(LedgerError::validation("max_entries_per_transaction must be at least 2"));
+        }
+        if !(1..=MAX_HOLD_TTL_SECS).contains(&self.hold_ttl_secs) {
+            return Err(LedgerError::validation(format!(
+                "hold_ttl_secs must be between 1 and {}",
+                MAX_HOLD_TTL_SECS
+            )));
+        }
+        if !(1..=MAX_INSTRUCTION_TTL_SECS).contains(&self.instruction_ttl_secs) {
+            return Err(LedgerError::validation(format!(
+                "instruction_ttl_secs must be between 1 and {}",
+                MAX_INSTRUCTION_TTL_SECS
+            )));
+        }
+        if !(1..=MAX_RETENTION_SECS).contains(&self.idempotency_retention_secs) {
+            return Err(LedgerError::validation(format!(
+                "idempotency_retention_secs must be between 1 and {}",
+                MAX_RETENTION_SECS
+            )));
+        }
+        if let Some((currency, _)) = self.approval_thresholds.iter().find(|(_, t)| **t < 0) {
+            return Err(LedgerError::validation(format!("Approval threshold for {} cannot be negative", currency)));
+        }
+        for (tier, limits) in &self.kyc_limits {
+            if [limits.max_balance, limits.single_transaction, limits.daily_debit].iter().flatten().any(|&v| v < 0) {
+                return Err(LedgerError::validation(format!("Limits of {} cannot be negative", tier)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn first_id() -> u64 {
+    1
+}
 
-/// Small utility to format kobo -> ₦x.yy
-fn format_naira(k: Kobo) -> String {
-    format!("₦{:.2}", (k as f64) / 100.0)
+fn first_custom_levy_rule_id() -> u64 {
+    default_levy_rules().len() as u64 + 1
 }
 
 #[derive(Debug, Serialize,Deserialize)]
@@ -16,223 +116,661 @@ fn format_naira(k: Kobo) -> String {
     pub transactions: Vec<Transaction>,
     pub next_account_id: u32,
     pub next_tx_id: u64,
-    pub bank_account_id:u32,
+    /// Internal bank accounts, one per role and currency.
+    #[serde(default)]
+    pub system_accounts: HashMap<SystemRole, HashMap<Currency, u32>>,
+    #[serde(default)]
+    pub fx_rates: RateTable,
+    #[serde(default)]
+    pub settings: LedgerSettings,
+    /// Authorization holds by id, including captured, released and expired ones.
+    #[serde(default)]
+    pub holds: BTreeMap<u64, Hold>,
+    #[serde(default = "first_id")]
+    pub next_hold_id: u64,
+    /// Responses to requests made with an `Idempotency-Key`, by key.
+    #[serde(default)]
+    pub idempotency_keys: HashMap<String, IdempotencyRecord>,
+    #[serde(default)]
+    pub standing_orders: BTreeMap<u64, StandingOrder>,
+    #[serde(default = "first_id")]
+    pub next_standing_order_id: u64,
+    #[serde(default)]
+    pub products: BTreeMap<String, Product>,
+    #[serde(default)]
+    pub interest_accruals: BTreeMap<u32, InterestAccrual>,
+    #[serde(default)]
+    pub fee_rules: BTreeMap<u64, FeeRule>,
+    #[serde(default = "first_id")]
+    pub next_fee_rule_id: u64,
+    /// Statutory levy rates by effective date; snapshots without any get the defaults.
+    #[serde(default = "default_levy_rules")]
+    pub levy_rules: Vec<LevyRule>,
+    #[serde(default = "first_custom_levy_rule_id")]
+    pub next_levy_rule_id: u64,
+    /// Next id for a lien; liens themselves live on their account.
+    #[serde(default = "first_id")]
+    pub next_lien_id: u64,
+    #[serde(default)]
+    pub customers: BTreeMap<u64, Customer>,
+    #[serde(default = "first_id")]
+    pub next_customer_id: u64,
+    /// Joint-account debits by id, including posted, rejected and expired ones.
+    #[serde(default)]
+    pub instructions: BTreeMap<u64, Instruction>,
+    #[serde(default = "first_id")]
+    pub next_instruction_id: u64,
+    /// Maker-checker queue, including decided requests.
+    #[serde(default)]
+    pub approval_requests: BTreeMap<u64, ApprovalRequest>,
+    #[serde(default = "first_id")]
+    pub next_approval_request_id: u64,
+    /// Changes to `settings`, oldest first.
+    #[serde(default)]
+    pub settings_changes: Vec<SettingsChange>,
+    /// Clearing account of snapshots taken before system accounts existed,
+    /// turned into the naira settlement account on load.
+    #[serde(rename = "bank_account_id", default, skip_serializing)]
+    pub legacy_bank_account_id: Option<u32>,
+    /// Rebuilt from `transactions` on load, see [`Ledger::rebuild_balance_index`].
+    #[serde(skip)]
+    pub balance_index: BalanceIndex,
  }
 impl Ledger {
     pub fn new() -> Self {
 
-        let mut accounts = HashMap::new();
-        let bank = Account {
-            id:0,
-            owner: "BANK".to_string(),
-            balance:0,
-            closed: false,
-            currency: Currency::NGN,
-            bank_name:"CBN".to_string(),
-            bank_code:"000".to_string(),
-            account_number:"00000000000".to_string(),
-        };
-        accounts.insert(0, bank);
-        Ledger { 
-            accounts,
+        let mut ledger = Ledger { 
+            accounts: HashMap::new(),
             transactions: Vec::new(),
-            next_account_id: 1,
+            next_account_id: 0,
             next_tx_id: 1, 
-            bank_account_id: 0,
+            system_accounts: HashMap::new(),
+            fx_rates: RateTable::default(),
+            settings: LedgerSettings::default(),
+            holds: BTreeMap::new(),
+            next_hold_id: 1,
+            idempotency_keys: HashMap::new(),
+            standing_orders: BTreeMap::new(),
+            next_standing_order_id: 1,
+            products: BTreeMap::new(),
+            interest_accruals: BTreeMap::new(),
+            fee_rules: BTreeMap::new(),
+            next_fee_rule_id: 1,
+            levy_rules: default_levy_rules(),
+            next_levy_rule_id: first_custom_levy_rule_id(),
+            next_lien_id: 1,
+            customers: BTreeMap::new(),
+            next_customer_id: 1,
+            instructions: BTreeMap::new(),
+            next_instruction_id: 1,
+            approval_requests: BTreeMap::new(),
+            next_approval_request_id: 1,
+            settings_changes: Vec::new(),
+            legacy_bank_account_id: None,
+            balance_index: BalanceIndex::default(),
+        };
+        // One settlement account per currency; the NGN one keeps id 0.
+        for currency in Currency::ALL {
+            ledger
+                .ensure_system_account(SystemRole::Settlement, currency)
+                .expect("fresh ledger has free account ids");
+        }
+        ledger
+    }
+
+    /// Returns the internal account for `role` in `currency`, opening it on first use.
+    pub fn ensure_system_account(&mut self, role: SystemRole, currency: Currency) -> Result<u32, LedgerError> {
+        if let Some(id) = self.system_account(role, currency) {
+            return Ok(id);
+        }
+        let id = self.next_account_id;
+        let account = Account {
+            id,
+            owner: role.owner().to_string(),
+            balance: Money::zero(currency),
+            status: AccountStatus::Active,
+            status_history: Vec::new(),
+            legacy_closed: false,
+            currency,
+            bank_name: "CBN".to_string(),
+            bank_code: "000".to_string(),
+            account_number: format!("{:011}", id),
+            account_type: role.account_type(),
+            system: true,
+            parent_id: None,
+            group: false,
+            overdraft_limit: 0,
+            product: None,
+            interest: None,
+            liens: Vec::new(),
+            kyc_tier: KycTier::Tier3,
+            kyc_history: Vec::new(),
+            customer_id: None,
+            mandate: None,
+        };
+        self.accounts.insert(id, account);
+        self.next_account_id = self
+            .next_account_id
+            .checked_add(1)
+            .ok_or(LedgerError::Overflow("Account id"))?;
+        self.system_accounts.entry(role).or_default().insert(currency, id);
+        Ok(id)
+    }
+
+    pub fn system_account(&self, role: SystemRole, currency: Currency) -> Option<u32> {
+        self.system_accounts.get(&role).and_then(|by_currency| by_currency.get(&currency)).copied()
+    }
+
+    /// Points `role` in `currency` at an existing account, e.g. to fund opening
+    /// balances from a specific equity account. The account is flagged as internal.
+    pub fn set_system_account(&mut self, role: SystemRole, currency: Currency, account_id: u32) -> Result<(), LedgerError> {
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.currency != currency {
+            return Err(LedgerError::CurrencyMismatch { expected: currency, found: acc.currency });
         }
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        acc.system = true;
+        self.system_accounts.entry(role).or_default().insert(currency, account_id);
+        Ok(())
+    }
+
+    pub fn settlement_account(&mut self, currency: Currency) -> Result<u32, LedgerError> {
+        self.ensure_system_account(SystemRole::Settlement, currency)
+    }
+
+    pub fn account_currency(&self, account_id: u32) -> Result<Currency, LedgerError> {
+        self.accounts
+            .get(&account_id)
+            .map(|acc| acc.currency)
+            .ok_or(LedgerError::account_not_found(account_id))
     }
 
+    /// Ensures `amount` is denominated in the currency of `account_id`.
+    pub(crate) fn check_currency(&self, account_id: u32, amount: Money) -> Result<Currency, LedgerError> {
+        let currency = self.account_currency(account_id)?;
+        if currency != amount.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: currency, found: amount.currency });
+        }
+        Ok(currency)
+    }
+
+    /// Opens a customer account in the currency of `initial_balance`. A
+    /// non-zero initial balance is posted as an opening-balance transaction
+    /// against the `OpeningBalance` equity account, within the tier 1 balance
+    /// cap; if that posting is rejected the account is not created.
     pub fn create_account(
         &mut self,
         owner: String,
-        initial_balance: Kobo, 
-        currency: Currency,
+        initial_balance: Money,
         bank_name: String,
         bank_code: String,
-    )-> Result<u32,String> {
-        let id =  self.next_account_id;
+    )-> Result<u32,LedgerError> {
+        self.open_account(owner, initial_balance, bank_name, bank_code, OpeningOptions::default())
+    }
+
+    /// Like [`Ledger::create_account`], also applying `options`. Either the
+    /// account is opened with all of them and its opening balance, or,
+    /// if any of them is rejected, not at all.
+    pub fn open_account(
+        &mut self,
+        owner: String,
+        initial_balance: Money,
+        bank_name: String,
+        bank_code: String,
+        options: OpeningOptions,
+    )-> Result<u32,LedgerError> {
          // Validate bank code
         if bank_code.len() != 3 || !bank_code.chars().all(|c| c.is_ascii_digit()) {
-            return Err("Bank code must be exactly 3 digits".to_string());
+            return Err(LedgerError::validation("Bank code must be exactly 3 digits"));
     }
+        if initial_balance.is_negative() {
+            return Err(LedgerError::validation("Initial balance cannot be negative"));
+        }
+        let currency = initial_balance.currency;
+        let funding_id = if initial_balance.is_zero() {
+            None
+        } else {
+            Some(self.ensure_system_account(SystemRole::OpeningBalance, currency)?)
+        };
+        let id =  self.next_account_id;
+        let next_id = id.checked_add(1).ok_or(LedgerError::Overflow("Account id"))?;
         // Generate account number: <bank_code><6-digit random>
-        let mut rng = thread_rng();
-        let random_number: u32 = rng.gen_range(0..10_000_000);  // 7 digits
+        let mut rng = rand::rng();
+        let random_number: u32 = rng.random_range(0..10_000_000);  // 7 digits
         let account_number = format!("{}{:07}", bank_code, random_number);
 
         let account = Account {
             id,
             owner,
-            balance: initial_balance,
-            closed: false,
+            balance: Money::zero(currency),
+            status: AccountStatus::Active,
+            status_history: Vec::new(),
+            legacy_closed: false,
             currency,
             bank_name,
             bank_code,
             account_number,
+            account_type: AccountType::Liability,
+            system: false,
+            parent_id: None,
+            group: false,
+            overdraft_limit: 0,
+            product: None,
+            interest: None,
+            liens: Vec::new(),
+            kyc_tier: KycTier::Tier1,
+            kyc_history: Vec::new(),
+            customer_id: None,
+            mandate: None,
         };
         self.accounts.insert(id, account);
-        self.next_account_id = self
-        .next_account_id
-        .checked_add(1)
-        .ok_or("Account id overflow")?;
+        self.next_account_id = next_id;
+
+        // The opening balance posts last, so until then a failure only has the account to undo
+        if let Err(e) = self.apply_opening_options(id, &options).and_then(|()| match funding_id {
+            Some(funding_id) => {
+                let entries = vec![
+                    TransactionEntry::credit(id, initial_balance),
+                    TransactionEntry::debit(funding_id, initial_balance),
+                ];
+                self.check_balance_cap(&entries[0])?;
+                self.record_transaction(Some("Opening balance".to_string()), entries).map(drop)
+            }
+            None => Ok(()),
+        }) {
+            self.accounts.remove(&id);
+            self.next_account_id = id;
+            return Err(e);
+        }
         Ok(id)
     }
-    pub fn close_account(&mut self, account_id: u32)-> Result<(),String> {
 
-        let acc = self
-        .accounts.get_mut(&account_id)
-        .ok_or_else(|| format!("Account {} not found",account_id))?;
-        if acc.balance != 0 {
-            return Err(format!("Cannot close acount {}: balance not zero ({}) ",account_id,format_naira(acc.balance)));
+    fn apply_opening_options(&mut self, id: u32, options: &OpeningOptions) -> Result<(), LedgerError> {
+        if options.parent_id.is_some() {
+            self.set_parent(id, options.parent_id)?;
+        }
+        if options.pending {
+            self.mark_pending(id, "Opened pending activation")?;
+        }
+        if let Some(customer_id) = options.customer_id {
+            self.attach_account(customer_id, id)?;
         }
-        acc.closed = true;
         Ok(())
     }
 
+    /// Posts a single-currency transaction. Every entry must touch an account
+    /// in the same currency; use [`Ledger::record_conversion`] to move value
+    /// between currencies.
     pub fn record_transaction(
         &mut self,
         description: Option<String>,
         entries: Vec<TransactionEntry>,
-    ) -> Result<u64,String>{
+    ) -> Result<u64,LedgerError>{
+        self.post(description, entries, None)
+    }
+
+    /// Posts an explicit currency conversion. Entries may span several
+    /// currencies, but each currency must still balance on its own.
+    pub fn record_conversion(
+        &mut self,
+        description: Option<String>,
+        entries: Vec<TransactionEntry>,
+        conversion: FxConversion,
+    ) -> Result<u64,LedgerError>{
+        self.post(description, entries, Some(conversion))
+    }
+
+    fn post(
+        &mut self,
+        description: Option<String>,
+        entries: Vec<TransactionEntry>,
+        fx: Option<FxConversion>,
+    ) -> Result<u64,LedgerError>{
 
-        if entries.is_empty() {
-            return Err("Entry must have at least 1 transaction".into());
+        if entries.len() < 2 {
+            return Err(LedgerError::validation("Transaction must have at least 2 entries"));
         }
-        let sum_debits: Kobo = entries.iter().map(|e| e.debit).sum();
-        let sum_credits: Kobo = entries.iter().map(|e| e.credit).sum();
-        if sum_debits != sum_credits {
-            return Err(format!(
-                "Unbalanced transactions debit:{} credit:{}",
-                sum_debits,sum_credits
-            ));
+        if entries.len() > self.settings.max_entries_per_transaction {
+            return Err(LedgerError::validation(format!(
+                "Transaction has {} entries, the maximum is {}",
+                entries.len(),
+                self.settings.max_entries_per_transaction
+            )));
         }
-        // Check accounts exist and are open
+        // Check entries are well-formed, accounts exist and are open, and group totals per currency
+        let mut seen = HashSet::new();
+        let mut totals: BTreeMap<Currency, (Money, Money)> = BTreeMap::new();
         for e in &entries {
-            if let Some(acc) = self.accounts.get(&e.account_id) {
-                if acc.closed {
-                    return Err(format!("Account {} is closed", e.account_id));
-                }
-            } 
-            else {
-                return Err(format!("Account {} does not exist", e.account_id));
+            if !e.amount.is_positive() {
+                return Err(LedgerError::validation(format!(
+                    "Entry for account {} must have a positive amount", e.account_id
+                )));
+            }
+            if !seen.insert(e.account_id) {
+                return Err(LedgerError::validation(format!(
+                    "Account {} appears more than once in the transaction", e.account_id
+                )));
+            }
+            let acc = self.accounts.get(&e.account_id).ok_or(LedgerError::account_not_found(e.account_id))?;
+            acc.ensure_can_post(e.side)?;
+            if acc.group {
+                return Err(LedgerError::validation(format!(
+                    "Account {} is a group account and cannot be posted to", e.account_id
+                )));
+            }
+            if e.amount.currency != acc.currency {
+                return Err(LedgerError::CurrencyMismatch { expected: acc.currency, found: e.amount.currency });
+            }
+            let (debits, credits) = totals
+                .entry(acc.currency)
+                .or_insert((Money::zero(acc.currency), Money::zero(acc.currency)));
+            match e.side {
+                Side::Debit => *debits = debits.checked_add(e.amount)?,
+                Side::Credit => *credits = credits.checked_add(e.amount)?,
+            }
+        }
+        if fx.is_none() && totals.len() > 1 {
+            let mut currencies = totals.keys();
+            let expected = *currencies.next().expect("at least one entry");
+            let found = *currencies.next().expect("more than one currency");
+            return Err(LedgerError::CurrencyMismatch { expected, found });
+        }
+        for (currency, (debits, credits)) in &totals {
+            if debits != credits {
+                return Err(LedgerError::Unbalanced {
+                    currency: *currency,
+                    debits: debits.amount_minor,
+                    credits: credits.amount_minor,
+                });
             }
         }
 
+        // Compute every new balance before applying any, so an overflow leaves the ledger untouched
+        let mut new_balances: HashMap<u32, Money> = HashMap::new();
         for e in &entries {
-            let acc = self
-            .accounts
-            .get_mut(&e.account_id)
-            .expect("account checked above");
-
-            acc.balance = acc.balance.checked_add(e.debit).and_then(|b| b.checked_sub(e.credit)).ok_or_else(|| "overflow applying entry".to_string())?;
+            let acc = &self.accounts[&e.account_id];
+            let current = new_balances.get(&e.account_id).copied().unwrap_or(acc.balance);
+            let updated = if e.side == acc.account_type.normal_balance() {
+                current.checked_add(e.amount)?
+            } else {
+                current.checked_sub(e.amount)?
+            };
+            new_balances.insert(e.account_id, updated);
+        }
+        for (&account_id, &balance) in &new_balances {
+            self.check_floor(account_id, balance)?;
         }
-
         let tx_id = self.next_tx_id;
-        
+        let timestamp = chrono::Utc::now();
+        for (account_id, balance) in new_balances {
+            self.accounts.get_mut(&account_id).expect("account checked above").balance = balance;
+            self.balance_index.record(account_id, BalancePoint { tx_id, timestamp, balance });
+        }
+
         let tx = Transaction{
             id: tx_id,
             description,
             entries,
-            timestamp: chrono::Utc::now(),
+            timestamp,
+            fx,
+            reverses: None,
+            reversed_by: None,
+            charges: Vec::new(),
+            approvals: Vec::new(),
         };
         self.transactions.push(tx);
-        self.next_tx_id = self.next_tx_id.checked_add(1).ok_or("Transaction id overflow")?;
+        self.next_tx_id = self.next_tx_id.checked_add(1).ok_or(LedgerError::Overflow("Transaction id"))?;
         Ok(tx_id)
 
     }
 
 
-    pub fn deposit(&mut self, to_id: u32, amount:Kobo,description: Option<String>)->Result<u64,String> {
-        let entries = vec![
-            TransactionEntry {
-                account_id:to_id,
-                debit:amount,
-                credit:0,
-            },
-            TransactionEntry {
-                account_id: self.bank_account_id,
-                debit:0,
-                credit:amount,
-            }
-        ];
+    /// The single floor rule every posting goes through: a customer account
+    /// whose balance goes down must stay within its overdraft limit after
+    /// active holds and liens are taken into account, and liens must remain
+    /// covered by the balance itself. System accounts have no floor, and
+    /// credits are always accepted so an account over its limit can be paid down.
+    fn check_floor(&self, account_id: u32, new_balance: Money) -> Result<(), LedgerError> {
+        let acc = &self.accounts[&account_id];
+        if acc.system || new_balance.amount_minor >= acc.balance.amount_minor {
+            return Ok(());
+        }
+        let liened = acc.liened_amount()?;
+        let unliened = new_balance.checked_sub(liened)?;
+        let headroom = unliened
+            .checked_sub(self.held_amount(account_id, acc.currency)?)?
+            .checked_add(Money::new(acc.overdraft_limit, acc.currency))?;
+        if headroom.is_negative() || (liened.is_positive() && unliened.is_negative()) {
+            return Err(LedgerError::InsufficientFunds {
+                account_id,
+                available: self.spendable_balance(account_id)?.amount_minor,
+                requested: acc.balance.amount_minor - new_balance.amount_minor,
+            });
+        }
+        Ok(())
+    }
+
+    /// Available balance plus the arranged overdraft: the most the account
+    /// can currently be debited by. The overdraft never covers liened funds.
+    pub fn spendable_balance(&self, account_id: u32) -> Result<Money, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        let with_overdraft = self
+            .available_balance(account_id)?
+            .checked_add(Money::new(acc.overdraft_limit, acc.currency))?;
+        let liened = acc.liened_amount()?;
+        if liened.is_zero() {
+            return Ok(with_overdraft);
+        }
+        let unliened = acc.balance.checked_sub(liened)?;
+        Ok(if unliened.amount_minor < with_overdraft.amount_minor { unliened } else { with_overdraft })
+    }
 
+    /// Sets the arranged overdraft on a customer account. A zero limit removes
+    /// it. Lowering the limit below the current overdrawn amount is allowed;
+    /// the account then only accepts credits until it is back within the limit.
+    pub fn set_overdraft_limit(&mut self, account_id: u32, limit: Money) -> Result<(), LedgerError> {
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and has no overdraft", account_id
+            )));
+        }
+        if acc.currency != limit.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: acc.currency, found: limit.currency });
+        }
+        if limit.is_negative() {
+            return Err(LedgerError::validation("Overdraft limit cannot be negative"));
+        }
+        acc.overdraft_limit = limit.amount_minor;
+        Ok(())
+    }
 
-        self.record_transaction(description, entries)
+    pub fn deposit(&mut self, to_id: u32, amount:Money,description: Option<String>)->Result<u64,LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Deposit amount must be positive"));
+        }
+        let currency = self.check_currency(to_id, amount)?;
+        let settlement_id = self.settlement_account(currency)?;
+        let mut entries = vec![
+            TransactionEntry::credit(to_id, amount),
+            TransactionEntry::debit(settlement_id, amount),
+        ];
+        let today = chrono::Utc::now().date_naive();
+        let charges = self.apply_levy(LevyKind::Emtl, to_id, amount, today, &mut entries)?;
+        self.check_tier_limits(amount, &entries)?;
+        self.record_charged_transaction(description, entries, charges.into_iter().collect())
     }
 
-    pub fn withdraw(&mut self, from_id: u32, amount:Kobo, description: Option<String>)-> Result<u64,String>
+    /// Withdraws from an account without a mandate; debits on joint
+    /// accounts go through [`Ledger::submit`].
+    pub fn withdraw(&mut self, from_id: u32, amount:Money, description: Option<String>)-> Result<u64,LedgerError>
     {
-        if amount <= 0 {
-            return Err("Withdrawal amount must be positive".into());
-        }
-        let bal = self.get_balance(from_id).ok_or("Account not found")?;
-        if bal < amount {
-            return Err("Insufficient funds".into());
-        }
-        let entries = vec![
-            TransactionEntry {
-                account_id:from_id,
-                debit:0,
-                credit:amount,
-            },
-            TransactionEntry {
-                account_id:self.bank_account_id,
-                debit:amount,
-                credit:0,
-            },
+        self.ensure_sole_operated(from_id)?;
+        self.post_withdrawal(from_id, amount, description)
+    }
+
+    pub(crate) fn post_withdrawal(&mut self, from_id: u32, amount: Money, description: Option<String>) -> Result<u64, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Withdrawal amount must be positive"));
+        }
+        let currency = self.check_currency(from_id, amount)?;
+        let settlement_id = self.settlement_account(currency)?;
+        let mut entries = vec![
+            TransactionEntry::debit(from_id, amount),
+            TransactionEntry::credit(settlement_id, amount),
         ];
-        self.record_transaction(description, entries)
-        
+        let charges = self.apply_fee(FeeOperation::Withdrawal, from_id, amount, &mut entries)?;
+        self.check_tier_limits(amount, &entries)?;
+        self.record_charged_transaction(description, entries, charges)
     }
 
-    pub fn transfer(&mut self, from_id: u32, to_id: u32, amount:Kobo, description: Option<String>)-> Result<u64, String>
+    /// Transfers from an account without a mandate; debits on joint
+    /// accounts go through [`Ledger::submit`].
+    pub fn transfer(&mut self, from_id: u32, to_id: u32, amount:Money, description: Option<String>)-> Result<u64, LedgerError>
     {
-        if amount <= 0 {
-            return Err("Transfer amount must be positive".into());
+        self.ensure_sole_operated(from_id)?;
+        self.post_transfer(from_id, to_id, amount, description)
+    }
+
+    pub(crate) fn post_transfer(&mut self, from_id: u32, to_id: u32, amount: Money, description: Option<String>) -> Result<u64, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Transfer amount must be positive"));
         }
 
         if from_id == to_id {
-            return Err("Cannot transfer to the same account".into());
+            return Err(LedgerError::validation("Cannot transfer to the same account"));
         }
 
 
-       let bal = self.get_balance(from_id).ok_or("Source account not found")?;
-        if bal < amount {
-            return Err("Insufficient funds".into());
-        }
+        self.check_currency(from_id, amount)?;
+        self.check_currency(to_id, amount)?;
 
-        let entries = vec![
-            TransactionEntry {
-                account_id: to_id,
-                debit: amount,
-                credit: 0,
-            },
-            TransactionEntry {
-                account_id: from_id,
-                debit: 0,
-                credit: amount,
-            },
+        let mut entries = vec![
+            TransactionEntry::credit(to_id, amount),
+            TransactionEntry::debit(from_id, amount),
         ];
-        self.record_transaction(description, entries)
+        let mut charges = self.apply_fee(FeeOperation::Transfer, from_id, amount, &mut entries)?;
+        let today = chrono::Utc::now().date_naive();
+        charges.extend(self.apply_levy(LevyKind::Emtl, to_id, amount, today, &mut entries)?);
+        self.check_tier_limits(amount, &entries)?;
+        self.record_charged_transaction(description, entries, charges)
+    }
+
+    /// Posts a transaction whose entries already include the legs for
+    /// `charges`, and records the charges on it.
+    pub(crate) fn record_charged_transaction(
+        &mut self,
+        description: Option<String>,
+        entries: Vec<TransactionEntry>,
+        charges: Vec<Charge>,
+    ) -> Result<u64, LedgerError> {
+        let tx_id = self.record_transaction(description, entries)?;
+        self.transactions.last_mut().expect("transaction just posted").charges = charges;
+        Ok(tx_id)
+    }
+
+    /// Posts the exact mirror of `tx_id`, linking the two transactions. A
+    /// transaction can be reversed once, and reversals themselves cannot be
+    /// reversed. Customer accounts may not be overdrawn by the reversal, and
+    /// it is held to their tier limits. Reversals debiting a joint account
+    /// go through [`Ledger::submit`].
+    pub fn reverse_transaction(&mut self, tx_id: u64, reason: &str) -> Result<u64, LedgerError> {
+        for e in self.reversal_entries(tx_id)? {
+            if e.side != self.accounts[&e.account_id].account_type.normal_balance() {
+                self.ensure_sole_operated(e.account_id)?;
+            }
+        }
+        self.post_reversal(tx_id, reason)
+    }
+
+    /// Entries mirroring `tx_id`, after checking it can be reversed.
+    pub(crate) fn reversal_entries(&self, tx_id: u64) -> Result<Vec<TransactionEntry>, LedgerError> {
+        let original = self.transaction(tx_id).ok_or(LedgerError::NotFound {
+            entity: "transaction",
+            id: tx_id.to_string(),
+        })?;
+        if let Some(reversal_id) = original.reversed_by {
+            return Err(LedgerError::validation(format!(
+                "Transaction {} was already reversed by transaction {}",
+                tx_id, reversal_id
+            )));
+        }
+        if let Some(reversed_id) = original.reverses {
+            return Err(LedgerError::validation(format!(
+                "Transaction {} is itself a reversal of transaction {}",
+                tx_id, reversed_id
+            )));
+        }
+        Ok(original
+            .entries
+            .iter()
+            .map(|e| TransactionEntry { account_id: e.account_id, side: e.side.opposite(), amount: e.amount })
+            .collect())
+    }
+
+    pub(crate) fn post_reversal(&mut self, tx_id: u64, reason: &str) -> Result<u64, LedgerError> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(LedgerError::validation("A reversal reason is required"));
+        }
+        let entries = self.reversal_entries(tx_id)?;
+        // Each leg is held to its own account's limits in its own currency
+        for e in &entries {
+            self.check_tier_limits(e.amount, std::slice::from_ref(e))?;
+        }
+        let fx = self.transaction(tx_id).expect("transaction checked above").fx.clone();
+
+        let description = Some(format!("Reversal of transaction {}: {}", tx_id, reason));
+        let reversal_id = self.post(description, entries, fx)?;
+        self.transactions.last_mut().expect("reversal just posted").reverses = Some(tx_id);
+        let index = self.transaction_index(tx_id).expect("transaction checked above");
+        self.transactions[index].reversed_by = Some(reversal_id);
+        Ok(reversal_id)
+    }
+
+    pub fn transaction(&self, tx_id: u64) -> Option<&Transaction> {
+        self.transaction_index(tx_id).map(|i| &self.transactions[i])
+    }
 
+    // Transactions are appended with increasing ids, so the log is sorted by id
+    fn transaction_index(&self, tx_id: u64) -> Option<usize> {
+        self.transactions.binary_search_by_key(&tx_id, |tx| tx.id).ok()
     }
 
-    pub fn get_balance(&self,account_id: u32)-> Option<Kobo> {
+    pub fn get_balance(&self,account_id: u32)-> Option<Money> {
         self.accounts.get(&account_id).map(|acc| acc.balance)
     }
 
-    pub fn find_account_by_owner(&self, name:&str)-> Option<&Account> {
-        self.accounts.values().find(|acc| acc.owner == name)
+    /// Every account whose free-text owner is `name`, by id. Prefer
+    /// [`Ledger::customer_accounts`] for accounts linked to a customer.
+    pub fn find_accounts_by_owner(&self, name: &str) -> Vec<&Account> {
+        let mut accounts: Vec<&Account> = self.accounts.values().filter(|acc| acc.owner == name).collect();
+        accounts.sort_by_key(|acc| acc.id);
+        accounts
     }
 
 
-    pub fn total_assets(&self) -> Kobo {
-        self.accounts.values().filter(|a| a.id != self.bank_account_id).map(|a| a.balance).sum()
-        
+    /// Sum of the balances of every account of `account_type`, one total per currency.
+    pub fn total_by_type(&self, account_type: AccountType) -> Result<Vec<Money>, LedgerError> {
+        let mut totals: BTreeMap<Currency, Money> = BTreeMap::new();
+        for a in self.accounts.values().filter(|a| a.account_type == account_type) {
+            let total = totals.entry(a.currency).or_insert(Money::zero(a.currency));
+            *total = total.checked_add(a.balance)?;
+        }
+        Ok(totals.into_values().collect())
+    }
+
+    pub fn total_assets(&self) -> Result<Vec<Money>, LedgerError> {
+        self.total_by_type(AccountType::Asset)
     }
 
     pub fn richest_account(&self)-> Option<&Account>{
-        self.accounts.values().filter(|a| a.id != self.bank_account_id).max_by_key(|a| a.balance)
+        self.accounts.values().filter(|a| !a.system).max_by_key(|a| a.balance.amount_minor)
 
     }
 
@@ -241,22 +779,153 @@ impl Ledger {
         self.transactions.iter().filter(|tx| tx.entries.iter().any(|e| e.account_id== account_id)).collect()
         
     }
-    pub async fn save_to_file(&self, path: &std::path::Path) -> Result<(),String> {
-        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
-        tokio::fs::write(path, json).await.map_err(|e| e.to_string())?;
+    /// Replaces the settings as `operator`, queuing new approval thresholds for a checker.
+    pub fn update_settings(&mut self, mut settings: LedgerSettings, operator: &str) -> Result<Option<u64>, LedgerError> {
+        let operator = operator.trim();
+        if operator.is_empty() {
+            return Err(LedgerError::validation("The operator changing the settings is required"));
+        }
+        settings.validate()?;
+        let thresholds =
+            std::mem::replace(&mut settings.approval_thresholds, self.settings.approval_thresholds.clone());
+        let approval_id = if thresholds != self.settings.approval_thresholds {
+            Some(self.queue_for_approval(Operation::ApprovalThresholds { thresholds }, None, None, operator)?)
+        } else {
+            None
+        };
+        if settings != self.settings {
+            self.apply_settings(settings, operator, None);
+        }
+        Ok(approval_id)
+    }
+
+    /// Puts `settings` in force and records who changed them.
+    pub(crate) fn apply_settings(&mut self, settings: LedgerSettings, made_by: &str, approved_by: Option<&str>) {
+        self.settings = settings;
+        self.settings_changes.push(SettingsChange {
+            made_by: made_by.to_string(),
+            approved_by: approved_by.map(str::to_string),
+            at: Utc::now(),
+            settings: self.settings.clone(),
+        });
+    }
+
+    /// Brings a freshly deserialized snapshot up to date: migrates fields of
+    /// older snapshots, rebuilds the indexes that are not persisted and posts
+    /// any balance the journal does not account for.
+    pub fn after_load(&mut self) -> Result<(), LedgerError> {
+        self.migrate_legacy_status();
+        self.migrate_legacy_amounts()?;
+        self.rebuild_balance_index()?;
+        self.post_unposted_balances()
+    }
+
+    /// Brings snapshots taken before amounts carried a currency up to date.
+    /// Their bare integer amounts were read as naira and are restated in the
+    /// currency of their account. Their bank account, whose balance went
+    /// negative as customers deposited, becomes the settlement asset account.
+    fn migrate_legacy_amounts(&mut self) -> Result<(), LedgerError> {
+        for acc in self.accounts.values_mut() {
+            acc.balance = Money::new(acc.balance.amount_minor, acc.currency);
+        }
+        for tx in &mut self.transactions {
+            for e in &mut tx.entries {
+                let acc = self.accounts.get(&e.account_id).ok_or(LedgerError::account_not_found(e.account_id))?;
+                e.amount = Money::new(e.amount.amount_minor, acc.currency);
+            }
+        }
+        let Some(bank_id) = self.legacy_bank_account_id.take() else {
+            return Ok(());
+        };
+        let bank = self.accounts.get_mut(&bank_id).ok_or(LedgerError::account_not_found(bank_id))?;
+        bank.system = true;
+        bank.account_type = AccountType::Asset;
+        bank.balance = Money::new(
+            bank.balance.amount_minor.checked_neg().ok_or(LedgerError::Overflow("Balance"))?,
+            bank.currency,
+        );
+        let currency = bank.currency;
+        self.system_accounts.entry(SystemRole::Settlement).or_default().insert(currency, bank_id);
+        for currency in Currency::ALL {
+            self.ensure_system_account(SystemRole::Settlement, currency)?;
+        }
+        Ok(())
+    }
+
+    /// Posts an opening-balance entry against `OpeningBalance` equity for
+    /// every account whose stored balance the journal does not account for,
+    /// such as initial balances of snapshots taken before those were posted,
+    /// so the balance history agrees with the balances. Runs after the
+    /// balance index is rebuilt; the stored balances are left as they are.
+    fn post_unposted_balances(&mut self) -> Result<(), LedgerError> {
+        let equity_ids: HashSet<u32> = self
+            .system_accounts
+            .get(&SystemRole::OpeningBalance)
+            .map(|by_currency| by_currency.values().copied().collect())
+            .unwrap_or_default();
+        let mut ids: Vec<u32> = self.accounts.keys().copied().filter(|id| !equity_ids.contains(id)).collect();
+        ids.sort_unstable();
+        for account_id in ids {
+            let acc = &self.accounts[&account_id];
+            let journal = self.balance_index.latest(account_id).unwrap_or(Money::zero(acc.currency));
+            let unposted = acc.balance.checked_sub(journal)?;
+            if unposted.is_zero() {
+                continue;
+            }
+            let (balance, normal) = (acc.balance, acc.account_type.normal_balance());
+            let (side, amount) = if unposted.is_positive() {
+                (normal, unposted)
+            } else {
+                (normal.opposite(), Money::zero(unposted.currency).checked_sub(unposted)?)
+            };
+
+            let equity_id = self.ensure_system_account(SystemRole::OpeningBalance, amount.currency)?;
+            let equity = &self.accounts[&equity_id];
+            let equity_balance = if side.opposite() == equity.account_type.normal_balance() {
+                equity.balance.checked_add(amount)?
+            } else {
+                equity.balance.checked_sub(amount)?
+            };
+            let tx_id = self.next_tx_id;
+            self.next_tx_id = tx_id.checked_add(1).ok_or(LedgerError::Overflow("Transaction id"))?;
+            let timestamp = Utc::now();
+            self.accounts.get_mut(&equity_id).expect("account just ensured").balance = equity_balance;
+            self.balance_index.record(account_id, BalancePoint { tx_id, timestamp, balance });
+            self.balance_index.record(equity_id, BalancePoint { tx_id, timestamp, balance: equity_balance });
+            self.transactions.push(Transaction {
+                id: tx_id,
+                description: Some("Opening balance (migrated)".to_string()),
+                entries: vec![
+                    TransactionEntry { account_id, side, amount },
+                    TransactionEntry { account_id: equity_id, side: side.opposite(), amount },
+                ],
+                timestamp,
+                fx: None,
+                reverses: None,
+                reversed_by: None,
+                charges: Vec::new(),
+                approvals: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    pub async fn save_to_file(&self, path: &std::path::Path) -> Result<(),LedgerError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| LedgerError::Persistence(e.to_string()))?;
+        tokio::fs::write(path, json).await.map_err(|e| LedgerError::Persistence(e.to_string()))?;
         Ok(())
     }
-    pub async fn load_from_file(path: &std::path::Path) -> Result<Self,String> {
-        let s = tokio::fs::read_to_string(path).await.map_err(|e| e.to_string())?;
-        let ledger: Ledger = serde_json::from_str(&s).map_err(|e| e.to_string())?;
+    pub async fn load_from_file(path: &std::path::Path) -> Result<Self,LedgerError> {
+        let s = tokio::fs::read_to_string(path).await.map_err(|e| LedgerError::Persistence(e.to_string()))?;
+        let mut ledger: Ledger = serde_json::from_str(&s).map_err(|e| LedgerError::Persistence(e.to_string()))?;
+        ledger.after_load()?;
         Ok(ledger)
     }
 
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
-   
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,193 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::SystemRole,
+    currency::Currency,
+    error::LedgerError,
+    fee::{share, MAX_BPS},
+    ledger::Ledger,
+    money::Money,
+    transaction::{Charge, ChargeKind, Side, TransactionEntry},
+};
+
+/// Statutory charges the bank collects on behalf of the government.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LevyKind {
+    /// Electronic Money Transfer Levy, borne by the recipient of an inbound credit.
+    Emtl,
+    /// Value added tax on fees, borne by the customer paying the fee.
+    Vat,
+    /// Withholding tax deducted from interest paid to customers.
+    Wht,
+}
+
+impl LevyKind {
+    pub const ALL: [LevyKind; 3] = [LevyKind::Emtl, LevyKind::Vat, LevyKind::Wht];
+
+    /// Liability account the levy is held in until it is remitted.
+    pub fn payable_role(self) -> SystemRole {
+        match self {
+            LevyKind::Emtl => SystemRole::EmtlPayable,
+            LevyKind::Vat => SystemRole::VatPayable,
+            LevyKind::Wht => SystemRole::WhtPayable,
+        }
+    }
+
+    /// The levy a charge collects, if it is one.
+    pub fn of(charge: ChargeKind) -> Option<LevyKind> {
+        match charge {
+            ChargeKind::Fee => None,
+            ChargeKind::Emtl => Some(LevyKind::Emtl),
+            ChargeKind::Vat => Some(LevyKind::Vat),
+            ChargeKind::Wht => Some(LevyKind::Wht),
+        }
+    }
+
+    pub fn charge_kind(self) -> ChargeKind {
+        match self {
+            LevyKind::Emtl => ChargeKind::Emtl,
+            LevyKind::Vat => ChargeKind::Vat,
+            LevyKind::Wht => ChargeKind::Wht,
+        }
+    }
+}
+
+/// Rate of a levy from a given date. A rule stays in force until a later
+/// rule for the same levy and currency takes effect, so a change in the law
+/// is recorded by adding a rule rather than editing one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LevyRule {
+    pub id: u64,
+    pub kind: LevyKind,
+    pub currency: Currency,
+    pub effective_from: NaiveDate,
+    /// Smallest base amount, in minor units, the levy applies to.
+    #[serde(default)]
+    pub threshold: i64,
+    /// Fixed levy in minor units.
+    #[serde(default)]
+    pub flat: i64,
+    /// Proportional levy in basis points of the base amount.
+    #[serde(default)]
+    pub bps: u32,
+}
+
+impl LevyRule {
+    /// Levy due on `base` minor units.
+    pub fn levy_for(&self, base: i64) -> Result<i64, LedgerError> {
+        if base < self.threshold {
+            return Ok(0);
+        }
+        self.flat.checked_add(share(base, self.bps)?).ok_or(LedgerError::Overflow("Levy"))
+    }
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).expect("valid date")
+}
+
+/// Naira levies under the Finance Act 2020: ₦50 EMTL on credits of
+/// ₦10,000 or more, 7.5% VAT on fees and 10% WHT on interest.
+pub fn default_levy_rules() -> Vec<LevyRule> {
+    let rule = |id, kind, effective_from, threshold, flat, bps| LevyRule {
+        id,
+        kind,
+        currency: Currency::NGN,
+        effective_from,
+        threshold,
+        flat,
+        bps,
+    };
+    vec![
+        rule(1, LevyKind::Emtl, date(2020, 1, 1), 1_000_000, 5_000, 0),
+        rule(2, LevyKind::Vat, date(2020, 2, 1), 0, 0, 750),
+        rule(3, LevyKind::Wht, date(2020, 1, 1), 0, 0, 1_000),
+    ]
+}
+
+impl Ledger {
+    /// Adds a rule taking effect on `effective_from`. Only one rule per levy
+    /// and currency may take effect on a given day.
+    pub fn add_levy_rule(
+        &mut self,
+        kind: LevyKind,
+        currency: Currency,
+        effective_from: NaiveDate,
+        threshold: i64,
+        flat: i64,
+        bps: u32,
+    ) -> Result<u64, LedgerError> {
+        if threshold < 0 || flat < 0 {
+            return Err(LedgerError::validation("Levy threshold and amount cannot be negative"));
+        }
+        if bps > MAX_BPS {
+            return Err(LedgerError::validation(format!("Levy rate must not exceed {} bps", MAX_BPS)));
+        }
+        if let Some(existing) = self
+            .levy_rules
+            .iter()
+            .find(|r| r.kind == kind && r.currency == currency && r.effective_from == effective_from)
+        {
+            return Err(LedgerError::validation(format!(
+                "Levy rule {} already takes effect on {}", existing.id, effective_from
+            )));
+        }
+        let id = self.next_levy_rule_id;
+        self.next_levy_rule_id = id.checked_add(1).ok_or(LedgerError::Overflow("Levy rule id"))?;
+        self.levy_rules.push(LevyRule { id, kind, currency, effective_from, threshold, flat, bps });
+        Ok(id)
+    }
+
+    pub fn remove_levy_rule(&mut self, id: u64) -> Result<LevyRule, LedgerError> {
+        let index = self.levy_rules.iter().position(|r| r.id == id).ok_or(LedgerError::NotFound {
+            entity: "levy rule",
+            id: id.to_string(),
+        })?;
+        Ok(self.levy_rules.remove(index))
+    }
+
+    /// Rule for `kind` in `currency` in force on `on`: the latest one that has
+    /// taken effect by then.
+    pub fn levy_rule_on(&self, kind: LevyKind, currency: Currency, on: NaiveDate) -> Option<&LevyRule> {
+        self.levy_rules
+            .iter()
+            .filter(|r| r.kind == kind && r.currency == currency && r.effective_from <= on)
+            .max_by_key(|r| r.effective_from)
+    }
+
+    /// Adds levy `kind` on `base` to `entries`, borne by `payer`: the payer's
+    /// debit leg grows, or its credit leg shrinks, by the levy, and a leg
+    /// crediting the levy's payable account is appended. Returns the charge to
+    /// record on the transaction, or `None` when nothing is due.
+    pub(crate) fn apply_levy(
+        &mut self,
+        kind: LevyKind,
+        payer: u32,
+        base: Money,
+        on: NaiveDate,
+        entries: &mut Vec<TransactionEntry>,
+    ) -> Result<Option<Charge>, LedgerError> {
+        if self.accounts.get(&payer).is_some_and(|acc| acc.system) {
+            return Ok(None);
+        }
+        let levy = match self.levy_rule_on(kind, base.currency, on) {
+            Some(rule) => Money::new(rule.levy_for(base.amount_minor)?, base.currency),
+            None => return Ok(None),
+        };
+        if !levy.is_positive() {
+            return Ok(None);
+        }
+        let payable = self.ensure_system_account(kind.payable_role(), base.currency)?;
+        let leg = entries
+            .iter_mut()
+            .find(|e| e.account_id == payer)
+            .expect("payer has a leg");
+        leg.amount = match leg.side {
+            Side::Debit => leg.amount.checked_add(levy)?,
+            Side::Credit => leg.amount.checked_sub(levy)?,
+        };
+        entries.push(TransactionEntry::credit(payable, levy));
+        Ok(Some(Charge { kind: kind.charge_kind(), payer, credit_account: payable, amount: levy }))
+    }
+}
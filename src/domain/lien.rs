@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    account::{Account, SystemRole},
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    transaction::TransactionEntry,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LienStatus {
+    Active,
+    Lifted,
+    /// The liened amount was debited to the lien-recovery account.
+    Enforced,
+    Expired,
+}
+
+/// An amount on an account blocked by order of a court or regulator. Unlike
+/// a hold it must be backed by the account's own funds, so an overdraft
+/// cannot be used to spend past it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Lien {
+    pub id: u64,
+    pub amount: Money,
+    pub reason: String,
+    /// Reference of the order the lien was placed under, e.g. a court case number.
+    pub reference: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub status: LienStatus,
+    /// When the lien was lifted or enforced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforce_tx_id: Option<u64>,
+}
+
+impl Lien {
+    /// Whether the lien still blocks funds at `now`.
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.status == LienStatus::Active && self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
+impl Account {
+    /// Liens still in force on the account.
+    pub fn active_liens(&self) -> impl Iterator<Item = &Lien> {
+        let now = Utc::now();
+        self.liens.iter().filter(move |l| l.is_active_at(now))
+    }
+
+    /// Total of the liens in force on the account.
+    pub fn liened_amount(&self) -> Result<Money, LedgerError> {
+        self.active_liens().try_fold(Money::zero(self.currency), |total, l| total.checked_add(l.amount))
+    }
+}
+
+impl Ledger {
+    /// Places a lien of `amount` on `account_id`. The lien may exceed the
+    /// current balance, in which case incoming funds stay blocked until it
+    /// is covered.
+    pub fn place_lien(
+        &mut self,
+        account_id: u32,
+        amount: Money,
+        reason: String,
+        reference: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<u64, LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Lien amount must be positive"));
+        }
+        if reason.trim().is_empty() || reference.trim().is_empty() {
+            return Err(LedgerError::validation("A lien needs a reason and a reference"));
+        }
+        let now = Utc::now();
+        if expires_at.is_some_and(|at| at <= now) {
+            return Err(LedgerError::validation("Lien expiry must be in the future"));
+        }
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and cannot be liened", account_id
+            )));
+        }
+        if acc.currency != amount.currency {
+            return Err(LedgerError::CurrencyMismatch { expected: acc.currency, found: amount.currency });
+        }
+
+        let id = self.next_lien_id;
+        self.next_lien_id = id.checked_add(1).ok_or(LedgerError::Overflow("Lien id"))?;
+        let acc = self.accounts.get_mut(&account_id).expect("account checked above");
+        acc.liens.push(Lien {
+            id,
+            amount,
+            reason,
+            reference,
+            created_at: now,
+            expires_at,
+            status: LienStatus::Active,
+            released_at: None,
+            enforce_tx_id: None,
+        });
+        Ok(id)
+    }
+
+    pub fn lien(&self, account_id: u32, lien_id: u64) -> Result<&Lien, LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        acc.liens.iter().find(|l| l.id == lien_id).ok_or(LedgerError::NotFound {
+            entity: "lien",
+            id: lien_id.to_string(),
+        })
+    }
+
+    fn active_lien_mut(&mut self, account_id: u32, lien_id: u64) -> Result<&mut Lien, LedgerError> {
+        self.expire_liens(Utc::now());
+        let lien = self.lien(account_id, lien_id)?;
+        if lien.status != LienStatus::Active {
+            return Err(LedgerError::validation(format!("Lien {} is {:?}", lien_id, lien.status)));
+        }
+        let acc = self.accounts.get_mut(&account_id).expect("lien checked above");
+        Ok(acc.liens.iter_mut().find(|l| l.id == lien_id).expect("lien checked above"))
+    }
+
+    /// Changes the amount, reason or expiry of an active lien.
+    pub fn amend_lien(
+        &mut self,
+        account_id: u32,
+        lien_id: u64,
+        amount: Option<Money>,
+        reason: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), LedgerError> {
+        let currency = self.account_currency(account_id)?;
+        if let Some(amount) = amount {
+            if !amount.is_positive() {
+                return Err(LedgerError::validation("Lien amount must be positive"));
+            }
+            if amount.currency != currency {
+                return Err(LedgerError::CurrencyMismatch { expected: currency, found: amount.currency });
+            }
+        }
+        if reason.as_ref().is_some_and(|r| r.trim().is_empty()) {
+            return Err(LedgerError::validation("A lien needs a reason and a reference"));
+        }
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(LedgerError::validation("Lien expiry must be in the future"));
+        }
+        let lien = self.active_lien_mut(account_id, lien_id)?;
+        if let Some(amount) = amount {
+            lien.amount = amount;
+        }
+        if let Some(reason) = reason {
+            lien.reason = reason;
+        }
+        if expires_at.is_some() {
+            lien.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    /// Releases the funds blocked by an active lien.
+    pub fn lift_lien(&mut self, account_id: u32, lien_id: u64) -> Result<(), LedgerError> {
+        let lien = self.active_lien_mut(account_id, lien_id)?;
+        lien.status = LienStatus::Lifted;
+        lien.released_at = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Debits the liened amount to the lien-recovery account, e.g. to pay a
+    /// court-ordered recovery. The lien is retired by the posting.
+    pub fn enforce_lien(&mut self, account_id: u32, lien_id: u64) -> Result<u64, LedgerError> {
+        let lien = self.active_lien_mut(account_id, lien_id)?;
+        let amount = lien.amount;
+        let description = Some(format!("Lien enforcement {}: {}", lien.reference, lien.reason));
+        // Only funds actually on the account back a lien; the overdraft never does
+        let balance = self.accounts[&account_id].balance;
+        if balance.amount_minor < amount.amount_minor {
+            return Err(LedgerError::InsufficientFunds {
+                account_id,
+                available: balance.amount_minor,
+                requested: amount.amount_minor,
+            });
+        }
+        // Retire the lien first so the floor check does not count it against its own enforcement
+        self.active_lien_mut(account_id, lien_id)?.status = LienStatus::Enforced;
+        let recovery = self.ensure_system_account(SystemRole::LienRecovery, amount.currency);
+        let posted = recovery.and_then(|recovery| {
+            let entries = vec![
+                TransactionEntry::debit(account_id, amount),
+                TransactionEntry::credit(recovery, amount),
+            ];
+            self.record_transaction(description, entries)
+        });
+        let acc = self.accounts.get_mut(&account_id).expect("lien checked above");
+        let lien = acc.liens.iter_mut().find(|l| l.id == lien_id).expect("lien checked above");
+        match posted {
+            Ok(tx_id) => {
+                lien.released_at = Some(Utc::now());
+                lien.enforce_tx_id = Some(tx_id);
+                Ok(tx_id)
+            }
+            Err(e) => {
+                lien.status = LienStatus::Active;
+                Err(e)
+            }
+        }
+    }
+
+    /// Marks every active lien past its expiry as expired. Returns how many
+    /// liens lapsed.
+    pub fn expire_liens(&mut self, now: DateTime<Utc>) -> usize {
+        let mut expired = 0;
+        for lien in self.accounts.values_mut().flat_map(|acc| acc.liens.iter_mut()) {
+            if lien.status == LienStatus::Active && lien.expires_at.is_some_and(|at| at <= now) {
+                lien.status = LienStatus::Expired;
+                expired += 1;
+            }
+        }
+        expired
+    }
+}
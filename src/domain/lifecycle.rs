@@ -0,0 +1,132 @@
+use chrono::Utc;
+
+use crate::domain::{
+    account::{AccountStatus, FreezeScope, StatusChange},
+    error::LedgerError,
+    ledger::Ledger,
+    standing_order::StandingOrderStatus,
+};
+
+impl Ledger {
+    /// Moves `account_id` to `to`, recording `reason` in its status history.
+    /// Returns the status it left.
+    fn change_status(&mut self, account_id: u32, to: AccountStatus, reason: &str) -> Result<AccountStatus, LedgerError> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(LedgerError::validation("A reason is required to change an account's status"));
+        }
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        let from = acc.status;
+        if !from.can_become(to) {
+            return Err(LedgerError::validation(format!(
+                "Account {} is {} and cannot become {}", account_id, from, to
+            )));
+        }
+        acc.status = to;
+        acc.status_history.push(StatusChange { from, to, reason: reason.to_string(), at: Utc::now() });
+        Ok(from)
+    }
+
+    fn expect_status(&self, account_id: u32, expected: &[AccountStatus]) -> Result<(), LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        let matches = expected.iter().any(|status| match (status, acc.status) {
+            (AccountStatus::Frozen { .. }, AccountStatus::Frozen { .. }) => true,
+            (expected, actual) => *expected == actual,
+        });
+        if !matches {
+            return Err(LedgerError::validation(format!("Account {} is {}", account_id, acc.status)));
+        }
+        Ok(())
+    }
+
+    /// Holds a newly opened account as pending until it is activated. Only an
+    /// account that has never changed status can be made pending.
+    pub fn mark_pending(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.status != AccountStatus::Active || !acc.status_history.is_empty() {
+            return Err(LedgerError::validation(format!(
+                "Only a newly opened account can be made pending, account {} is {}", account_id, acc.status
+            )));
+        }
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(LedgerError::validation("A reason is required to change an account's status"));
+        }
+        let acc = self.accounts.get_mut(&account_id).expect("account checked above");
+        acc.status = AccountStatus::Pending;
+        acc.status_history.push(StatusChange {
+            from: AccountStatus::Active,
+            to: AccountStatus::Pending,
+            reason: reason.to_string(),
+            at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Activates a pending account or reactivates a dormant one.
+    pub fn activate_account(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        self.expect_status(account_id, &[AccountStatus::Pending, AccountStatus::Dormant])?;
+        self.change_status(account_id, AccountStatus::Active, reason).map(drop)
+    }
+
+    pub fn mark_dormant(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        self.change_status(account_id, AccountStatus::Dormant, reason).map(drop)
+    }
+
+    /// Freezes the account for debits, credits or both. A frozen account can
+    /// be frozen again with a different scope.
+    pub fn freeze_account(&mut self, account_id: u32, scope: FreezeScope, reason: &str) -> Result<(), LedgerError> {
+        self.change_status(account_id, AccountStatus::Frozen { scope }, reason).map(drop)
+    }
+
+    pub fn unfreeze_account(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        self.expect_status(account_id, &[AccountStatus::Frozen { scope: FreezeScope::Total }])?;
+        self.change_status(account_id, AccountStatus::Active, reason).map(drop)
+    }
+
+    /// Closes an account whose balance is zero.
+    pub fn close_account(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if !acc.balance.is_zero() {
+            return Err(LedgerError::BalanceNotZero { account_id, balance: acc.balance.amount_minor });
+        }
+        if acc.active_liens().next().is_some() {
+            return Err(LedgerError::validation(format!(
+                "Account {} has active liens; lift them before closing it",
+                account_id
+            )));
+        }
+        let now = Utc::now();
+        if self.holds.values().any(|h| h.account_id == account_id && h.is_active_at(now)) {
+            return Err(LedgerError::validation(format!(
+                "Account {} has active holds; release them before closing it",
+                account_id
+            )));
+        }
+        if self.standing_orders.values().any(|o| {
+            (o.from == account_id || o.to == account_id)
+                && matches!(o.status, StandingOrderStatus::Active | StandingOrderStatus::Paused)
+        }) {
+            return Err(LedgerError::validation(format!(
+                "Account {} has standing orders; cancel them before closing it",
+                account_id
+            )));
+        }
+        self.change_status(account_id, AccountStatus::Closed, reason).map(drop)
+    }
+
+    pub fn reopen_account(&mut self, account_id: u32, reason: &str) -> Result<(), LedgerError> {
+        self.expect_status(account_id, &[AccountStatus::Closed])?;
+        self.change_status(account_id, AccountStatus::Active, reason).map(drop)
+    }
+
+    /// Folds the `closed` flag of accounts read from an older snapshot into
+    /// their status.
+    pub(crate) fn migrate_legacy_status(&mut self) {
+        for acc in self.accounts.values_mut() {
+            if std::mem::take(&mut acc.legacy_closed) && acc.status == AccountStatus::Active {
+                acc.status = AccountStatus::Closed;
+            }
+        }
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    standing_order::{FailurePolicy, Frequency, StandingOrderStatus},
+    transaction::{Approval, Approver, Side, TransactionEntry},
+};
+
+/// How many of an account's signatories must approve a debit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum MandateRule {
+    AnyOne,
+    All,
+    NOfM { required: usize },
+}
+
+/// Operating instructions of a joint account: the customers who sign for it
+/// and how many of them must approve each debit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Mandate {
+    /// Customer ids of the signatories.
+    pub signatories: Vec<u64>,
+    pub rule: MandateRule,
+}
+
+impl Mandate {
+    /// Number of distinct signatory approvals a debit needs.
+    pub fn required(&self) -> usize {
+        match self.rule {
+            MandateRule::AnyOne => 1,
+            MandateRule::All => self.signatories.len(),
+            MandateRule::NOfM { required } => required,
+        }
+    }
+
+    pub fn is_signatory(&self, customer_id: u64) -> bool {
+        self.signatories.contains(&customer_id)
+    }
+}
+
+/// A debit, or an arrangement to debit later, that can be carried out now
+/// or held back for approval, or a change to the approval thresholds, which
+/// always is.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum Operation {
+    Withdrawal { account_id: u32, amount: Money },
+    Transfer { from: u32, to: u32, amount: Money },
+    /// A transfer into an account in another currency; `amount` is in the
+    /// currency of `from`.
+    FxTransfer { from: u32, to: u32, amount: Money },
+    /// A manual journal entered by an operator. Mandates do not apply to it.
+    Journal { entries: Vec<TransactionEntry> },
+    /// New approval thresholds, entered through the settings, see
+    /// [`Ledger::update_settings`]. Never posted.
+    ApprovalThresholds { thresholds: BTreeMap<Currency, i64> },
+    /// The reversal of transaction `tx_id`, see [`Ledger::reverse_transaction`].
+    Reversal { tx_id: u64, reason: String },
+    /// An authorization hold, see [`Ledger::authorize`]. It lapses after
+    /// `ttl_secs`, or the `hold_ttl_secs` setting, from when it is placed.
+    Hold {
+        account_id: u32,
+        amount: Money,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl_secs: Option<i64>,
+    },
+    /// The capture of hold `hold_id`, see [`Ledger::capture`]; the full hold
+    /// when `amount` is `None`.
+    Capture {
+        hold_id: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        amount: Option<Money>,
+    },
+    /// A standing order, see [`Ledger::create_standing_order`].
+    StandingOrder {
+        from: u32,
+        to: u32,
+        amount: Money,
+        frequency: Frequency,
+        start: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        end: Option<DateTime<Utc>>,
+        policy: FailurePolicy,
+    },
+    /// The enforcement of lien `lien_id` on `account_id`, see [`Ledger::enforce_lien`].
+    LienEnforcement { account_id: u32, lien_id: u64 },
+}
+
+fn thresholds_not_posted() -> LedgerError {
+    LedgerError::validation("Approval thresholds are changed through the settings")
+}
+
+fn not_posted() -> LedgerError {
+    LedgerError::validation("Holds and standing orders are not posted directly")
+}
+
+/// Lifetime of a hold requested in seconds.
+fn hold_ttl(secs: i64) -> Result<Duration, LedgerError> {
+    Duration::try_seconds(secs).ok_or_else(|| LedgerError::validation("Hold lifetime is out of range"))
+}
+
+/// Number of approvals given by signatories, as opposed to operators.
+fn signatory_approvals(approvals: &[Approval]) -> usize {
+    approvals.iter().filter(|a| matches!(a.approver, Approver::Signatory(_))).count()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InstructionStatus {
+    /// Waiting for signatory approvals.
+    Pending,
+    Posted,
+    Rejected,
+    /// Not approved in time.
+    Expired,
+}
+
+/// A debit on a joint account waiting for its signatories.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Instruction {
+    pub id: u64,
+    /// The joint account debited.
+    pub account_id: u32,
+    pub operation: Operation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: InstructionStatus,
+    /// Signatory approvals so far, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<Approval>,
+    /// Signatory who rejected the instruction, and why.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejected_by: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    /// Transaction posted once enough signatories approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<u64>,
+}
+
+/// What became of a submitted debit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    Posted { tx_id: u64 },
+    /// Held as an instruction until the signatories approve it.
+    Pending { instruction_id: u64 },
+    /// Queued for a checker, see [`ApprovalRequest`](crate::domain::approval::ApprovalRequest).
+    AwaitingApproval { approval_id: u64 },
+    /// An authorization hold was placed.
+    Held { hold_id: u64 },
+    /// A standing order was set up.
+    Scheduled { standing_order_id: u64 },
+}
+
+impl Ledger {
+    /// Puts a joint account under a mandate. Every signatory must be a
+    /// customer, and the account's own customer, if any, must be one of them.
+    pub fn set_mandate(&mut self, account_id: u32, signatories: Vec<u64>, rule: MandateRule) -> Result<(), LedgerError> {
+        let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.is_closed() {
+            return Err(LedgerError::Closed(account_id));
+        }
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and cannot have a mandate", account_id
+            )));
+        }
+        let mandate = Mandate { signatories, rule };
+        if mandate.signatories.len() < 2 {
+            return Err(LedgerError::validation("A joint account needs at least two signatories"));
+        }
+        for (i, &customer_id) in mandate.signatories.iter().enumerate() {
+            self.customer(customer_id)?;
+            if mandate.signatories[..i].contains(&customer_id) {
+                return Err(LedgerError::validation(format!("Customer {} is listed twice", customer_id)));
+            }
+        }
+        if !(1..=mandate.signatories.len()).contains(&mandate.required()) {
+            return Err(LedgerError::validation(format!(
+                "A mandate of {} signatories cannot require {} approvals",
+                mandate.signatories.len(),
+                mandate.required()
+            )));
+        }
+        if let Some(holder) = acc.customer_id
+            && !mandate.is_signatory(holder)
+        {
+            return Err(LedgerError::validation(format!(
+                "Customer {} holds account {} and must be a signatory", holder, account_id
+            )));
+        }
+        self.ensure_no_pending_instructions(account_id)?;
+        // Standing orders debit without approval, so none may pay out of a joint account
+        if let Some(order) = self.standing_orders.values().find(|o| {
+            o.from == account_id && matches!(o.status, StandingOrderStatus::Active | StandingOrderStatus::Paused)
+        }) {
+            return Err(LedgerError::validation(format!(
+                "Standing order {} pays out of account {}; cancel it before adding a mandate", order.id, account_id
+            )));
+        }
+        self.accounts.get_mut(&account_id).expect("account checked above").mandate = Some(mandate);
+        Ok(())
+    }
+
+    /// Returns the account to sole operation.
+    pub fn clear_mandate(&mut self, account_id: u32) -> Result<(), LedgerError> {
+        self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        self.ensure_no_pending_instructions(account_id)?;
+        self.accounts.get_mut(&account_id).expect("account checked above").mandate = None;
+        Ok(())
+    }
+
+    fn ensure_no_pending_instructions(&mut self, account_id: u32) -> Result<(), LedgerError> {
+        self.expire_instructions(Utc::now());
+        match self.instructions.values().find(|i| {
+            i.status == InstructionStatus::Pending && i.account_id == account_id
+        }) {
+            Some(pending) => Err(LedgerError::validation(format!(
+                "Instruction {} on account {} is still pending", pending.id, account_id
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails when `account_id` may only be debited through an approved
+    /// instruction, see [`Ledger::submit`].
+    pub(crate) fn ensure_sole_operated(&self, account_id: u32) -> Result<(), LedgerError> {
+        match self.accounts.get(&account_id) {
+            Some(acc) if acc.mandate.is_some() => Err(LedgerError::validation(format!(
+                "Account {} is operated under a mandate; debits need signatory approval", account_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Customer account `operation` debits, whose mandate applies to it;
+    /// `None` for a journal or a lien enforcement, which no mandate covers,
+    /// for holds and standing orders, which joint accounts refuse anyway,
+    /// or a settings change. Of the accounts a reversal debits, one under a
+    /// mandate is preferred.
+    pub fn debited_account(&self, operation: &Operation) -> Result<Option<u32>, LedgerError> {
+        Ok(match operation {
+            Operation::Withdrawal { account_id, .. } => Some(*account_id),
+            Operation::Transfer { from, .. } | Operation::FxTransfer { from, .. } => Some(*from),
+            Operation::Reversal { tx_id, .. } => {
+                let debited: Vec<u32> = self
+                    .reversal_entries(*tx_id)?
+                    .iter()
+                    .filter(|e| {
+                        let acc = &self.accounts[&e.account_id];
+                        !acc.system && e.side != acc.account_type.normal_balance()
+                    })
+                    .map(|e| e.account_id)
+                    .collect();
+                debited
+                    .iter()
+                    .find(|id| self.accounts[id].mandate.is_some())
+                    .or(debited.first())
+                    .copied()
+            }
+            Operation::Journal { .. }
+            | Operation::ApprovalThresholds { .. }
+            | Operation::Hold { .. }
+            | Operation::Capture { .. }
+            | Operation::StandingOrder { .. }
+            | Operation::LienEnforcement { .. } => None,
+        })
+    }
+
+    /// Posts `operation`, recording `approvals` on the resulting transaction.
+    pub(crate) fn execute(
+        &mut self,
+        operation: &Operation,
+        description: Option<String>,
+        approvals: Vec<Approval>,
+    ) -> Result<u64, LedgerError> {
+        let tx_id = match operation {
+            Operation::Withdrawal { account_id, amount } => self.post_withdrawal(*account_id, *amount, description)?,
+            Operation::Transfer { from, to, amount } => self.post_transfer(*from, *to, *amount, description)?,
+            Operation::FxTransfer { from, to, amount } => self.post_fx_transfer(*from, *to, *amount, description)?,
+            Operation::Journal { entries } => self.record_transaction(description, entries.clone())?,
+            Operation::Reversal { tx_id, reason } => self.post_reversal(*tx_id, reason)?,
+            Operation::Capture { hold_id, amount } => self.capture(*hold_id, *amount, description)?,
+            Operation::LienEnforcement { account_id, lien_id } => self.enforce_lien(*account_id, *lien_id)?,
+            Operation::ApprovalThresholds { .. } => return Err(thresholds_not_posted()),
+            Operation::Hold { .. } | Operation::StandingOrder { .. } => return Err(not_posted()),
+        };
+        // A capture already carries the approval of its hold
+        if !approvals.is_empty() {
+            self.transactions.last_mut().expect("transaction just posted").approvals = approvals;
+        }
+        Ok(tx_id)
+    }
+
+    /// Submits an operation. Operations above the approval threshold wait
+    /// for a checker, see [`Ledger::approve_request`], and need `maker`, the
+    /// operator entering them. Otherwise debits on accounts without a
+    /// mandate post straight away, while on a joint account they become an
+    /// instruction that posts once enough signatories approve it. `signatory`
+    /// is the customer giving the instruction, whose approval counts towards
+    /// the mandate.
+    pub fn submit(
+        &mut self,
+        operation: Operation,
+        description: Option<String>,
+        signatory: Option<u64>,
+        maker: Option<&str>,
+    ) -> Result<Submission, LedgerError> {
+        if self.requires_approval(&operation)? {
+            let maker = maker.map(str::trim).filter(|m| !m.is_empty()).ok_or_else(|| {
+                LedgerError::validation("Operations above the approval threshold need a maker")
+            })?;
+            self.validate_operation(&operation, signatory)?;
+            let approval_id = self.queue_for_approval(operation, description, signatory, maker)?;
+            return Ok(Submission::AwaitingApproval { approval_id });
+        }
+        self.carry_out(operation, description, signatory, Vec::new())
+    }
+
+    /// Checks an operation can be accepted for later posting. Balances are
+    /// only checked when it posts.
+    pub(crate) fn validate_operation(&self, operation: &Operation, signatory: Option<u64>) -> Result<(), LedgerError> {
+        match operation {
+            Operation::Withdrawal { amount, .. }
+            | Operation::Transfer { amount, .. }
+            | Operation::FxTransfer { amount, .. }
+                if !amount.is_positive() =>
+            {
+                return Err(LedgerError::validation("Amount must be positive"));
+            }
+            Operation::Transfer { from, to, .. } | Operation::FxTransfer { from, to, .. } if from == to => {
+                return Err(LedgerError::validation("Cannot transfer to the same account"));
+            }
+            Operation::FxTransfer { from, to, amount } => {
+                self.check_currency(*from, *amount)?;
+                self.fx_quote(*amount, self.account_currency(*to)?)?;
+            }
+            Operation::Transfer { from, to, amount } => {
+                self.check_currency(*from, *amount)?;
+                self.check_currency(*to, *amount)?;
+            }
+            Operation::Withdrawal { account_id, amount } => {
+                self.check_currency(*account_id, *amount)?;
+            }
+            Operation::Journal { entries } => {
+                if entries.len() < 2 {
+                    return Err(LedgerError::validation("Transaction must have at least 2 entries"));
+                }
+                for e in entries {
+                    if !e.amount.is_positive() {
+                        return Err(LedgerError::validation(format!(
+                            "Entry for account {} must have a positive amount", e.account_id
+                        )));
+                    }
+                    self.check_currency(e.account_id, e.amount)?;
+                }
+                let total = |side: Side| {
+                    entries
+                        .iter()
+                        .filter(|e| e.side == side)
+                        .try_fold(Money::zero(entries[0].amount.currency), |total, e| total.checked_add(e.amount))
+                };
+                let (debits, credits) = (total(Side::Debit)?, total(Side::Credit)?);
+                if debits != credits {
+                    return Err(LedgerError::Unbalanced {
+                        currency: debits.currency,
+                        debits: debits.amount_minor,
+                        credits: credits.amount_minor,
+                    });
+                }
+            }
+            Operation::Reversal { tx_id, reason } => {
+                if reason.trim().is_empty() {
+                    return Err(LedgerError::validation("A reversal reason is required"));
+                }
+                self.reversal_entries(*tx_id)?;
+            }
+            Operation::Hold { account_id, amount, ttl_secs } => {
+                self.hold_lifetime(*account_id, *amount, ttl_secs.map(hold_ttl).transpose()?)?;
+            }
+            Operation::Capture { hold_id, amount } => {
+                self.capture_amount(*hold_id, *amount)?;
+            }
+            Operation::StandingOrder { from, to, amount, start, end, policy, .. } => {
+                self.check_standing_order(*from, *to, *amount, *start, *end, *policy)?;
+            }
+            Operation::LienEnforcement { account_id, lien_id } => {
+                let lien = self.lien(*account_id, *lien_id)?;
+                if !lien.is_active_at(Utc::now()) {
+                    return Err(LedgerError::validation(format!("Lien {} is not active", lien_id)));
+                }
+            }
+            Operation::ApprovalThresholds { .. } => return Err(thresholds_not_posted()),
+        }
+        if let Some(customer_id) = signatory {
+            let account_id = self
+                .debited_account(operation)?
+                .ok_or_else(|| LedgerError::validation("Journals are not signed by customers"))?;
+            if !self.accounts[&account_id].mandate.as_ref().is_some_and(|m| m.is_signatory(customer_id)) {
+                return Err(LedgerError::validation(format!(
+                    "Customer {} is not a signatory of account {}", customer_id, account_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Carries out an accepted operation: places a hold or sets up a standing
+    /// order, recording the checker's approval on it, or posts the operation
+    /// through [`Ledger::dispatch`].
+    pub(crate) fn carry_out(
+        &mut self,
+        operation: Operation,
+        description: Option<String>,
+        signatory: Option<u64>,
+        approvals: Vec<Approval>,
+    ) -> Result<Submission, LedgerError> {
+        match operation {
+            Operation::Hold { account_id, amount, ttl_secs } => {
+                let hold_id = self.authorize(account_id, amount, description, ttl_secs.map(hold_ttl).transpose()?)?;
+                self.holds.get_mut(&hold_id).expect("hold just placed").approval = approvals.into_iter().next();
+                Ok(Submission::Held { hold_id })
+            }
+            Operation::StandingOrder { from, to, amount, frequency, start, end, policy } => {
+                let id = self.create_standing_order(from, to, amount, description, frequency, start, end, policy)?;
+                self.standing_orders.get_mut(&id).expect("order just created").approval = approvals.into_iter().next();
+                Ok(Submission::Scheduled { standing_order_id: id })
+            }
+            operation => self.dispatch(operation, description, signatory, approvals),
+        }
+    }
+
+    /// Posts `operation`, or holds it as an instruction when it debits a
+    /// joint account. `approvals` already given, e.g. by a checker, are
+    /// recorded ahead of the signatories'.
+    pub(crate) fn dispatch(
+        &mut self,
+        operation: Operation,
+        description: Option<String>,
+        signatory: Option<u64>,
+        mut approvals: Vec<Approval>,
+    ) -> Result<Submission, LedgerError> {
+        let mandate = match self.debited_account(&operation)? {
+            Some(account_id) => self
+                .accounts
+                .get(&account_id)
+                .ok_or(LedgerError::account_not_found(account_id))?
+                .mandate
+                .clone()
+                .map(|mandate| (account_id, mandate)),
+            None => None,
+        };
+        let Some((account_id, mandate)) = mandate else {
+            let tx_id = self.execute(&operation, description, approvals)?;
+            return Ok(Submission::Posted { tx_id });
+        };
+        self.validate_operation(&operation, signatory)?;
+        let now = Utc::now();
+        if let Some(customer_id) = signatory {
+            approvals.push(Approval { approver: Approver::Signatory(customer_id), at: now });
+        }
+        if signatory_approvals(&approvals) >= mandate.required() {
+            let tx_id = self.execute(&operation, description, approvals)?;
+            return Ok(Submission::Posted { tx_id });
+        }
+
+        let expires_at = Duration::try_seconds(self.settings.instruction_ttl_secs)
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .ok_or_else(|| LedgerError::validation("instruction_ttl_secs is out of range"))?;
+        let id = self.next_instruction_id;
+        self.next_instruction_id = id.checked_add(1).ok_or(LedgerError::Overflow("Instruction id"))?;
+        self.instructions.insert(id, Instruction {
+            id,
+            account_id,
+            operation,
+            description,
+            created_at: now,
+            expires_at,
+            status: InstructionStatus::Pending,
+            approvals,
+            rejected_by: None,
+            rejection_reason: None,
+            tx_id: None,
+        });
+        Ok(Submission::Pending { instruction_id: id })
+    }
+
+    pub fn instruction(&self, id: u64) -> Result<&Instruction, LedgerError> {
+        self.instructions.get(&id).ok_or(LedgerError::NotFound {
+            entity: "instruction",
+            id: id.to_string(),
+        })
+    }
+
+    /// The pending instruction `id` with the mandate of its account, after
+    /// checking `signatory` may act on it.
+    fn pending_instruction(&mut self, id: u64, signatory: u64) -> Result<(Instruction, Mandate), LedgerError> {
+        self.expire_instructions(Utc::now());
+        let instruction = self.instruction(id)?;
+        if instruction.status != InstructionStatus::Pending {
+            return Err(LedgerError::validation(format!("Instruction {} is {:?}", id, instruction.status)));
+        }
+        let account_id = instruction.account_id;
+        let mandate = self.accounts[&account_id].mandate.clone().ok_or_else(|| {
+            LedgerError::validation(format!("Account {} no longer has a mandate", account_id))
+        })?;
+        if !mandate.is_signatory(signatory) {
+            return Err(LedgerError::validation(format!(
+                "Customer {} is not a signatory of account {}", signatory, account_id
+            )));
+        }
+        Ok((instruction.clone(), mandate))
+    }
+
+    /// Records `signatory`'s approval of instruction `id` and posts it once
+    /// the mandate is met. If the posting fails, e.g. for lack of funds, the
+    /// approval is not recorded and the instruction stays pending.
+    pub fn approve_instruction(&mut self, id: u64, signatory: u64) -> Result<&Instruction, LedgerError> {
+        let (instruction, mandate) = self.pending_instruction(id, signatory)?;
+        if instruction.approvals.iter().any(|a| a.approver == Approver::Signatory(signatory)) {
+            return Err(LedgerError::validation(format!(
+                "Customer {} has already approved instruction {}", signatory, id
+            )));
+        }
+        let mut approvals = instruction.approvals;
+        approvals.push(Approval { approver: Approver::Signatory(signatory), at: Utc::now() });
+        let tx_id = if signatory_approvals(&approvals) >= mandate.required() {
+            Some(self.execute(&instruction.operation, instruction.description, approvals.clone())?)
+        } else {
+            None
+        };
+        let instruction = self.instructions.get_mut(&id).expect("instruction checked above");
+        instruction.approvals = approvals;
+        if tx_id.is_some() {
+            instruction.status = InstructionStatus::Posted;
+            instruction.tx_id = tx_id;
+        }
+        Ok(instruction)
+    }
+
+    /// Any one signatory can stop an instruction.
+    pub fn reject_instruction(&mut self, id: u64, signatory: u64, reason: &str) -> Result<&Instruction, LedgerError> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(LedgerError::validation("A rejection reason is required"));
+        }
+        self.pending_instruction(id, signatory)?;
+        let instruction = self.instructions.get_mut(&id).expect("instruction checked above");
+        instruction.status = InstructionStatus::Rejected;
+        instruction.rejected_by = Some(signatory);
+        instruction.rejection_reason = Some(reason.to_string());
+        Ok(instruction)
+    }
+
+    /// Marks every pending instruction past its expiry as expired. Returns
+    /// the ids of the instructions that lapsed.
+    pub fn expire_instructions(&mut self, now: DateTime<Utc>) -> Vec<u64> {
+        let mut expired = Vec::new();
+        for instruction in self.instructions.values_mut() {
+            if instruction.status == InstructionStatus::Pending && instruction.expires_at <= now {
+                instruction.status = InstructionStatus::Expired;
+                expired.push(instruction.id);
+            }
+        }
+        expired
+    }
+}
@@ -1,4 +1,23 @@
 pub mod account;
+pub mod approval;
 pub mod transaction;
 pub mod ledger;
-pub mod currency;
\ No newline at end of file
+pub mod currency;
+pub mod customer;
+pub mod error;
+pub mod fee;
+pub mod fx;
+pub mod hierarchy;
+pub mod history;
+pub mod hold;
+pub mod idempotency;
+pub mod interest;
+pub mod kyc;
+pub mod levy;
+pub mod lien;
+pub mod lifecycle;
+pub mod mandate;
+pub mod money;
+pub mod product;
+pub mod reports;
+pub mod standing_order;
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+use crate::domain::{currency::Currency, error::LedgerError};
+
+/// An amount of a specific currency, held as an integer count of the
+/// currency's minor unit (kobo, cents, ...).
+///
+/// Serializes as `{"amount_minor": 125050, "currency": "NGN"}`. When
+/// deserializing, the amount may be given as `amount_minor` or `amount`, and
+/// either as minor units (`125050`) or as a decimal string (`"1250.50"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Money {
+    pub amount_minor: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount_minor: i64, currency: Currency) -> Self {
+        Money { amount_minor, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Money::new(0, currency)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount_minor == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.amount_minor > 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount_minor < 0
+    }
+
+    /// Adds two amounts of the same currency.
+    pub fn checked_add(self, other: Money) -> Result<Money, LedgerError> {
+        self.same_currency(&other)?;
+        self.amount_minor
+            .checked_add(other.amount_minor)
+            .map(|amount| Money::new(amount, self.currency))
+            .ok_or(LedgerError::Overflow("Amount"))
+    }
+
+    /// Subtracts an amount of the same currency.
+    pub fn checked_sub(self, other: Money) -> Result<Money, LedgerError> {
+        self.same_currency(&other)?;
+        self.amount_minor
+            .checked_sub(other.amount_minor)
+            .map(|amount| Money::new(amount, self.currency))
+            .ok_or(LedgerError::Overflow("Amount"))
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), LedgerError> {
+        if self.currency != other.currency {
+            return Err(LedgerError::CurrencyMismatch {
+                expected: self.currency,
+                found: other.currency,
+            });
+        }
+        Ok(())
+    }
+
+    /// Parses a decimal string in major units, e.g. `"1250.50"` NGN into
+    /// 125050 kobo. More fractional digits than the currency allows are rejected.
+    pub fn parse_decimal(s: &str, currency: Currency) -> Result<Money, LedgerError> {
+        let invalid = || LedgerError::validation(format!("Invalid {} amount '{}'", currency, s));
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let exponent = currency.minor_units() as usize;
+        if whole.is_empty()
+            || !whole.chars().all(|c| c.is_ascii_digit())
+            || !frac.chars().all(|c| c.is_ascii_digit())
+            || frac.len() > exponent
+            || (digits.contains('.') && frac.is_empty())
+        {
+            return Err(invalid());
+        }
+        let scale = 10i64.pow(exponent as u32);
+        let whole: i64 = whole.parse().map_err(|_| invalid())?;
+        let frac: i64 = if frac.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", frac, width = exponent).parse().map_err(|_| invalid())?
+        };
+        let amount = whole
+            .checked_mul(scale)
+            .and_then(|a| a.checked_add(frac))
+            .ok_or(LedgerError::Overflow("Amount"))?;
+        Ok(Money::new(if negative { -amount } else { amount }, currency))
+    }
+
+    /// Decimal string in major units, e.g. `"1250.50"`.
+    pub fn to_decimal_string(&self) -> String {
+        let exponent = self.currency.minor_units();
+        let sign = if self.is_negative() { "-" } else { "" };
+        let abs = self.amount_minor.unsigned_abs();
+        if exponent == 0 {
+            return format!("{}{}", sign, abs);
+        }
+        let scale = 10u64.pow(exponent);
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            abs / scale,
+            abs % scale,
+            width = exponent as usize
+        )
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimal = self.to_decimal_string();
+        match decimal.strip_prefix('-') {
+            Some(abs) => write!(f, "-{}{}", self.currency.symbol(), abs),
+            None => write!(f, "{}{}", self.currency.symbol(), decimal),
+        }
+    }
+}
+
+/// An amount supplied by a client without a currency: either an integer count
+/// of minor units (`125050`) or a decimal string in major units (`"1250.50"`).
+/// The currency comes from context, usually the account being posted to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Amount {
+    Minor(i64),
+    Decimal(String),
+}
+
+impl Amount {
+    pub fn to_money(&self, currency: Currency) -> Result<Money, LedgerError> {
+        match self {
+            Amount::Minor(amount) => Ok(Money::new(*amount, currency)),
+            Amount::Decimal(s) => Money::parse_decimal(s, currency),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MoneyRepr {
+    #[serde(alias = "amount_minor")]
+    amount: Amount,
+    currency: Currency,
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MoneyRepr::deserialize(deserializer)?;
+        repr.amount
+            .to_money(repr.currency)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Reads a [`Money`], or the bare integer of minor units written by snapshots
+/// taken before amounts carried their currency. The bare integer is read as
+/// naira; [`Ledger::after_load`](crate::domain::ledger::Ledger::after_load)
+/// restates it in the currency of the account it belongs to.
+pub(crate) fn money_or_legacy_minor<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Legacy(i64),
+        Money(Money),
+    }
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Legacy(amount) => Money::new(amount, Currency::NGN),
+        Repr::Money(money) => money,
+    })
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{error::LedgerError, interest::InterestTerms, ledger::Ledger};
+
+/// A deposit product (savings, current, ...) whose terms apply to every
+/// account opened under it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Product {
+    pub code: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interest: Option<InterestTerms>,
+}
+
+impl Ledger {
+    /// Inserts or replaces the product with `product.code`.
+    pub fn upsert_product(&mut self, product: Product) -> Result<(), LedgerError> {
+        if product.code.trim().is_empty() {
+            return Err(LedgerError::validation("Product code must not be empty"));
+        }
+        if let Some(terms) = &product.interest {
+            terms.validate()?;
+        }
+        self.products.insert(product.code.clone(), product);
+        Ok(())
+    }
+
+    pub fn product(&self, code: &str) -> Result<&Product, LedgerError> {
+        self.products.get(code).ok_or_else(|| LedgerError::NotFound {
+            entity: "product",
+            id: code.to_string(),
+        })
+    }
+
+    /// Puts `account_id` under product `code`, or removes it from its product.
+    pub fn assign_product(&mut self, account_id: u32, code: Option<String>) -> Result<(), LedgerError> {
+        if let Some(code) = &code {
+            self.product(code)?;
+        }
+        let acc = self.accounts.get_mut(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+        if acc.system || acc.group {
+            return Err(LedgerError::validation(format!(
+                "Account {} is an internal account and cannot hold a product", account_id
+            )));
+        }
+        acc.product = code;
+        Ok(())
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::{
+    account::AccountType,
+    currency::Currency,
+    error::LedgerError,
+    ledger::Ledger,
+    money::Money,
+    levy::LevyKind,
+    transaction::{Side, Transaction},
+};
+
+/// Debit and credit activity of one account over the report period.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TrialBalanceLine {
+    pub account_id: u32,
+    pub owner: String,
+    pub account_type: AccountType,
+    pub debits: Money,
+    pub credits: Money,
+}
+
+/// Trial balance of the accounts held in one currency.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CurrencyTrialBalance {
+    pub currency: Currency,
+    pub lines: Vec<TrialBalanceLine>,
+    pub total_debits: Money,
+    pub total_credits: Money,
+    pub balanced: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TrialBalance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    pub currencies: Vec<CurrencyTrialBalance>,
+    /// True when debits equal credits in every currency.
+    pub balanced: bool,
+}
+
+impl TrialBalance {
+    /// One row per account, amounts as decimal strings in major units.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("currency,account_id,owner,account_type,debits,credits\n");
+        for section in &self.currencies {
+            for line in &section.lines {
+                csv.push_str(&format!(
+                    "{},{},{},{:?},{},{}\n",
+                    section.currency,
+                    line.account_id,
+                    csv_field(&line.owner),
+                    line.account_type,
+                    line.debits.to_decimal_string(),
+                    line.credits.to_decimal_string(),
+                ));
+            }
+            csv.push_str(&format!(
+                "{},,TOTAL,,{},{}\n",
+                section.currency,
+                section.total_debits.to_decimal_string(),
+                section.total_credits.to_decimal_string(),
+            ));
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One levy in one currency over the report period.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LevyRemittanceLine {
+    pub kind: LevyKind,
+    pub currency: Currency,
+    pub payable_account: u32,
+    /// Number of charges collected in the period.
+    pub charges: usize,
+    pub collected: Money,
+    /// Charges given back by reversals posted in the period.
+    pub refunded: Money,
+    /// Collected less refunded: what the period adds to the amount owed.
+    pub net: Money,
+    /// Balance of the payable account at the end of the period: everything
+    /// collected and not yet remitted.
+    pub outstanding: Money,
+}
+
+/// Statutory levies owed to the regulator, per levy and currency.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LevyRemittance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    pub lines: Vec<LevyRemittanceLine>,
+}
+
+impl LevyRemittance {
+    /// One row per levy and currency, amounts as decimal strings in major units.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("levy,currency,payable_account,charges,collected,refunded,net,outstanding\n");
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{:?},{},{},{},{},{},{},{}\n",
+                line.kind,
+                line.currency,
+                line.payable_account,
+                line.charges,
+                line.collected.to_decimal_string(),
+                line.refunded.to_decimal_string(),
+                line.net.to_decimal_string(),
+                line.outstanding.to_decimal_string(),
+            ));
+        }
+        csv
+    }
+}
+
+impl Ledger {
+    /// Transactions timestamped within `from..=to`, either bound optional.
+    fn transactions_between(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<&[Transaction], LedgerError> {
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err(LedgerError::validation("Report period starts after it ends"));
+        }
+        // The journal is in posting order, so the period is a contiguous slice
+        let start = from.map_or(0, |from| self.transactions.partition_point(|tx| tx.timestamp < from));
+        let end = to.map_or(self.transactions.len(), |to| self.transactions.partition_point(|tx| tx.timestamp <= to));
+        Ok(self.transactions.get(start..end).unwrap_or_default())
+    }
+
+    /// Debit and credit totals of every posting account for transactions
+    /// timestamped within `from..=to` (either bound optional), per currency.
+    pub fn trial_balance(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<TrialBalance, LedgerError> {
+        let period = self.transactions_between(from, to)?;
+
+        let mut lines: BTreeMap<u32, TrialBalanceLine> = self
+            .accounts
+            .values()
+            .filter(|acc| !acc.group)
+            .map(|acc| {
+                (acc.id, TrialBalanceLine {
+                    account_id: acc.id,
+                    owner: acc.owner.clone(),
+                    account_type: acc.account_type,
+                    debits: Money::zero(acc.currency),
+                    credits: Money::zero(acc.currency),
+                })
+            })
+            .collect();
+        for tx in period {
+            for e in &tx.entries {
+                let line = lines.get_mut(&e.account_id).ok_or(LedgerError::account_not_found(e.account_id))?;
+                match e.side {
+                    Side::Debit => line.debits = line.debits.checked_add(e.amount)?,
+                    Side::Credit => line.credits = line.credits.checked_add(e.amount)?,
+                }
+            }
+        }
+
+        let mut sections: BTreeMap<Currency, CurrencyTrialBalance> = BTreeMap::new();
+        for line in lines.into_values() {
+            let currency = line.debits.currency;
+            let section = sections.entry(currency).or_insert_with(|| CurrencyTrialBalance {
+                currency,
+                lines: Vec::new(),
+                total_debits: Money::zero(currency),
+                total_credits: Money::zero(currency),
+                balanced: true,
+            });
+            section.total_debits = section.total_debits.checked_add(line.debits)?;
+            section.total_credits = section.total_credits.checked_add(line.credits)?;
+            section.lines.push(line);
+        }
+        let mut currencies: Vec<CurrencyTrialBalance> = sections.into_values().collect();
+        for section in &mut currencies {
+            section.balanced = section.total_debits == section.total_credits;
+        }
+        Ok(TrialBalance {
+            from,
+            to,
+            balanced: currencies.iter().all(|c| c.balanced),
+            currencies,
+        })
+    }
+}
+
+/// One account on a financial statement, on its normal side.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatementLine {
+    pub account_id: u32,
+    pub owner: String,
+    pub amount: Money,
+}
+
+/// A block of accounts of one type, e.g. the assets on a balance sheet.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatementSection {
+    pub lines: Vec<StatementLine>,
+    pub total: Money,
+}
+
+impl StatementSection {
+    fn new(currency: Currency) -> Self {
+        StatementSection { lines: Vec::new(), total: Money::zero(currency) }
+    }
+
+    fn push(&mut self, line: StatementLine) -> Result<(), LedgerError> {
+        self.total = self.total.checked_add(line.amount)?;
+        self.lines.push(line);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CurrencyBalanceSheet {
+    pub currency: Currency,
+    pub assets: StatementSection,
+    pub liabilities: StatementSection,
+    pub equity: StatementSection,
+    /// Income less expenses not yet closed to equity; part of total equity.
+    pub current_earnings: Money,
+    pub total_equity: Money,
+    pub total_liabilities_and_equity: Money,
+    /// True when assets equal liabilities plus equity.
+    pub balanced: bool,
+}
+
+/// Balance sheet totals restated in a single currency.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BalanceSheetSummary {
+    pub currency: Currency,
+    pub total_assets: Money,
+    pub total_liabilities: Money,
+    pub total_equity: Money,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BalanceSheet {
+    pub as_of: DateTime<Utc>,
+    pub currencies: Vec<CurrencyBalanceSheet>,
+    /// Totals converted at mid rates, when a base currency was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub converted: Option<BalanceSheetSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CurrencyIncomeStatement {
+    pub currency: Currency,
+    pub income: StatementSection,
+    pub expenses: StatementSection,
+    pub net_income: Money,
+}
+
+/// Income statement totals restated in a single currency.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IncomeStatementSummary {
+    pub currency: Currency,
+    pub total_income: Money,
+    pub total_expenses: Money,
+    pub net_income: Money,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IncomeStatement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    pub currencies: Vec<CurrencyIncomeStatement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub converted: Option<IncomeStatementSummary>,
+}
+
+impl Ledger {
+    /// Assets, liabilities and equity as they stood at `as_of`, per currency.
+    /// Accounts with a zero balance are left out. With `base`, the totals are
+    /// also restated in that currency at current mid rates.
+    pub fn balance_sheet(&self, as_of: DateTime<Utc>, base: Option<Currency>) -> Result<BalanceSheet, LedgerError> {
+        let mut accounts: Vec<_> = self.accounts.values().filter(|acc| !acc.group).collect();
+        accounts.sort_by_key(|acc| acc.id);
+
+        let mut sheets: BTreeMap<Currency, CurrencyBalanceSheet> = BTreeMap::new();
+        for acc in accounts {
+            let balance = self.balance_as_of(acc.id, as_of)?;
+            if balance.is_zero() {
+                continue;
+            }
+            let currency = acc.currency;
+            let sheet = sheets.entry(currency).or_insert_with(|| CurrencyBalanceSheet {
+                currency,
+                assets: StatementSection::new(currency),
+                liabilities: StatementSection::new(currency),
+                equity: StatementSection::new(currency),
+                current_earnings: Money::zero(currency),
+                total_equity: Money::zero(currency),
+                total_liabilities_and_equity: Money::zero(currency),
+                balanced: true,
+            });
+            let line = StatementLine { account_id: acc.id, owner: acc.owner.clone(), amount: balance };
+            match acc.account_type {
+                AccountType::Asset => sheet.assets.push(line)?,
+                AccountType::Liability => sheet.liabilities.push(line)?,
+                AccountType::Equity => sheet.equity.push(line)?,
+                AccountType::Income => sheet.current_earnings = sheet.current_earnings.checked_add(balance)?,
+                AccountType::Expense => sheet.current_earnings = sheet.current_earnings.checked_sub(balance)?,
+            }
+        }
+        let mut currencies: Vec<CurrencyBalanceSheet> = sheets.into_values().collect();
+        for sheet in &mut currencies {
+            sheet.total_equity = sheet.equity.total.checked_add(sheet.current_earnings)?;
+            sheet.total_liabilities_and_equity = sheet.liabilities.total.checked_add(sheet.total_equity)?;
+            sheet.balanced = sheet.assets.total == sheet.total_liabilities_and_equity;
+        }
+
+        let converted = match base {
+            Some(base) => {
+                let mut summary = BalanceSheetSummary {
+                    currency: base,
+                    total_assets: Money::zero(base),
+                    total_liabilities: Money::zero(base),
+                    total_equity: Money::zero(base),
+                };
+                for sheet in &currencies {
+                    summary.total_assets = summary.total_assets.checked_add(self.value_at_mid(sheet.assets.total, base)?)?;
+                    summary.total_liabilities = summary
+                        .total_liabilities
+                        .checked_add(self.value_at_mid(sheet.liabilities.total, base)?)?;
+                    summary.total_equity = summary.total_equity.checked_add(self.value_at_mid(sheet.total_equity, base)?)?;
+                }
+                Some(summary)
+            }
+            None => None,
+        };
+        Ok(BalanceSheet { as_of, currencies, converted })
+    }
+
+    /// Income and expenses for transactions within `from..=to`, per currency.
+    /// Accounts without activity in the period are left out.
+    pub fn income_statement(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        base: Option<Currency>,
+    ) -> Result<IncomeStatement, LedgerError> {
+        let trial_balance = self.trial_balance(from, to)?;
+        let mut currencies = Vec::new();
+        for section in trial_balance.currencies {
+            let currency = section.currency;
+            let mut statement = CurrencyIncomeStatement {
+                currency,
+                income: StatementSection::new(currency),
+                expenses: StatementSection::new(currency),
+                net_income: Money::zero(currency),
+            };
+            for line in section.lines {
+                let (target, amount) = match line.account_type {
+                    AccountType::Income => (&mut statement.income, line.credits.checked_sub(line.debits)?),
+                    AccountType::Expense => (&mut statement.expenses, line.debits.checked_sub(line.credits)?),
+                    _ => continue,
+                };
+                if line.debits.is_zero() && line.credits.is_zero() {
+                    continue;
+                }
+                target.push(StatementLine { account_id: line.account_id, owner: line.owner, amount })?;
+            }
+            if statement.income.lines.is_empty() && statement.expenses.lines.is_empty() {
+                continue;
+            }
+            statement.net_income = statement.income.total.checked_sub(statement.expenses.total)?;
+            currencies.push(statement);
+        }
+
+        let converted = match base {
+            Some(base) => {
+                let mut summary = IncomeStatementSummary {
+                    currency: base,
+                    total_income: Money::zero(base),
+                    total_expenses: Money::zero(base),
+                    net_income: Money::zero(base),
+                };
+                for statement in &currencies {
+                    summary.total_income = summary.total_income.checked_add(self.value_at_mid(statement.income.total, base)?)?;
+                    summary.total_expenses = summary
+                        .total_expenses
+                        .checked_add(self.value_at_mid(statement.expenses.total, base)?)?;
+                }
+                summary.net_income = summary.total_income.checked_sub(summary.total_expenses)?;
+                Some(summary)
+            }
+            None => None,
+        };
+        Ok(IncomeStatement { from, to, currencies, converted })
+    }
+
+    /// Levies collected by transactions posted within `from..=to`, net of
+    /// reversals posted in the same period, and the amount outstanding on each
+    /// payable account at the end of the period.
+    pub fn levy_remittance(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<LevyRemittance, LedgerError> {
+        let period = self.transactions_between(from, to)?;
+        let mut lines: BTreeMap<(LevyKind, Currency), LevyRemittanceLine> = BTreeMap::new();
+        for kind in LevyKind::ALL {
+            for currency in Currency::ALL {
+                let Some(payable_account) = self.system_account(kind.payable_role(), currency) else { continue };
+                let outstanding = match to {
+                    Some(to) => self.balance_as_of(payable_account, to)?,
+                    None => self.get_balance(payable_account).ok_or(LedgerError::account_not_found(payable_account))?,
+                };
+                lines.insert((kind, currency), LevyRemittanceLine {
+                    kind,
+                    currency,
+                    payable_account,
+                    charges: 0,
+                    collected: Money::zero(currency),
+                    refunded: Money::zero(currency),
+                    net: Money::zero(currency),
+                    outstanding,
+                });
+            }
+        }
+        for tx in period {
+            for charge in &tx.charges {
+                let Some(kind) = LevyKind::of(charge.kind) else { continue };
+                if let Some(line) = lines.get_mut(&(kind, charge.amount.currency)) {
+                    line.charges += 1;
+                    line.collected = line.collected.checked_add(charge.amount)?;
+                }
+            }
+            let Some(original) = tx.reverses.and_then(|id| self.transaction(id)) else { continue };
+            for charge in &original.charges {
+                let Some(kind) = LevyKind::of(charge.kind) else { continue };
+                if let Some(line) = lines.get_mut(&(kind, charge.amount.currency)) {
+                    line.refunded = line.refunded.checked_add(charge.amount)?;
+                }
+            }
+        }
+        let mut lines: Vec<LevyRemittanceLine> = lines.into_values().collect();
+        for line in &mut lines {
+            line.net = line.collected.checked_sub(line.refunded)?;
+        }
+        Ok(LevyRemittance { from, to, lines })
+    }
+}
@@ -0,0 +1,350 @@
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    error::LedgerError,
+    ledger::Ledger,
+    mandate::Operation,
+    money::Money,
+    transaction::Approval,
+};
+
+/// Longest wait between retries of an occurrence.
+const MAX_RETRY_INTERVAL_SECS: i64 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Frequency {
+    /// A single future-dated transfer.
+    Once,
+    Daily,
+    Weekly,
+    /// Same day each month as the start date, or the last day of shorter months.
+    Monthly,
+}
+
+/// What to do when an occurrence cannot be paid for lack of funds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Give up on the occurrence and wait for the next one.
+    Skip,
+    /// Try again every `interval_secs`, up to `max_attempts` attempts in
+    /// total, then skip the occurrence.
+    Retry { max_attempts: u32, interval_secs: i64 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StandingOrderStatus {
+    Active,
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    Posted,
+    /// Insufficient funds; another attempt is scheduled.
+    Retrying,
+    /// The occurrence was abandoned.
+    Skipped,
+    /// The payment is above the approval threshold and was queued for a checker.
+    AwaitingApproval,
+}
+
+/// One attempt at paying an occurrence of a standing order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StandingOrderExecution {
+    pub order_id: u64,
+    pub from: u32,
+    pub to: u32,
+    pub amount: Money,
+    /// When the occurrence was due.
+    pub scheduled_for: DateTime<Utc>,
+    pub executed_at: DateTime<Utc>,
+    pub outcome: ExecutionOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An instruction to transfer a fixed amount between two accounts on a schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StandingOrder {
+    pub id: u64,
+    pub from: u32,
+    pub to: u32,
+    pub amount: Money,
+    pub description: Option<String>,
+    pub frequency: Frequency,
+    pub policy: FailurePolicy,
+    /// First occurrence; later occurrences are derived from it.
+    pub start: DateTime<Utc>,
+    /// No occurrence is scheduled after this instant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    pub status: StandingOrderStatus,
+    /// Index of the occurrence currently due, counting from 0 at `start`.
+    pub occurrence: u32,
+    /// When the order should next be attempted: the due occurrence or a retry.
+    pub next_run: DateTime<Utc>,
+    /// Failed attempts at the current occurrence.
+    pub attempts: u32,
+    pub executions: Vec<StandingOrderExecution>,
+    /// Checker who approved the order when it was above the approval
+    /// threshold. Its payments need no further approval and record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<Approval>,
+}
+
+impl StandingOrder {
+    /// Due date of occurrence `n`, or `None` past the end of the schedule.
+    fn occurrence_at(&self, n: u32) -> Option<DateTime<Utc>> {
+        let at = match self.frequency {
+            Frequency::Once if n > 0 => return None,
+            Frequency::Once => self.start,
+            Frequency::Daily => self.start.checked_add_signed(Duration::days(n as i64))?,
+            Frequency::Weekly => self.start.checked_add_signed(Duration::weeks(n as i64))?,
+            // Always offset from the start so a 31st anchor survives February
+            Frequency::Monthly => self.start.checked_add_months(Months::new(n))?,
+        };
+        match self.end {
+            Some(end) if at > end => None,
+            _ => Some(at),
+        }
+    }
+
+    /// Moves on to the next occurrence, completing the order when there is none.
+    fn advance(&mut self) {
+        self.attempts = 0;
+        self.occurrence += 1;
+        match self.occurrence_at(self.occurrence) {
+            Some(at) => self.next_run = at,
+            None => self.status = StandingOrderStatus::Completed,
+        }
+    }
+}
+
+impl Ledger {
+    /// Sets up a standing order paying `amount` from `from` to `to`. Orders
+    /// above the approval threshold are submitted as
+    /// [`Operation::StandingOrder`] for a checker instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_standing_order(
+        &mut self,
+        from: u32,
+        to: u32,
+        amount: Money,
+        description: Option<String>,
+        frequency: Frequency,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        policy: FailurePolicy,
+    ) -> Result<u64, LedgerError> {
+        self.check_standing_order(from, to, amount, start, end, policy)?;
+        let id = self.next_standing_order_id;
+        self.next_standing_order_id = id.checked_add(1).ok_or(LedgerError::Overflow("Standing order id"))?;
+        self.standing_orders.insert(id, StandingOrder {
+            id,
+            from,
+            to,
+            amount,
+            description,
+            frequency,
+            policy,
+            start,
+            end,
+            status: StandingOrderStatus::Active,
+            occurrence: 0,
+            next_run: start,
+            attempts: 0,
+            executions: Vec::new(),
+            approval: None,
+        });
+        Ok(id)
+    }
+
+    /// Checks a standing order can be set up, leaving the balance aside.
+    pub(crate) fn check_standing_order(
+        &self,
+        from: u32,
+        to: u32,
+        amount: Money,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        policy: FailurePolicy,
+    ) -> Result<(), LedgerError> {
+        if !amount.is_positive() {
+            return Err(LedgerError::validation("Standing order amount must be positive"));
+        }
+        if from == to {
+            return Err(LedgerError::validation("Cannot transfer to the same account"));
+        }
+        for account_id in [from, to] {
+            let acc = self.accounts.get(&account_id).ok_or(LedgerError::account_not_found(account_id))?;
+            if acc.is_closed() {
+                return Err(LedgerError::Closed(account_id));
+            }
+            if acc.currency != amount.currency {
+                return Err(LedgerError::CurrencyMismatch { expected: acc.currency, found: amount.currency });
+            }
+        }
+        // Each payment would need the signatories' approval, and none is at hand when it runs
+        self.ensure_sole_operated(from)?;
+        if end.is_some_and(|end| end < start) {
+            return Err(LedgerError::validation("Standing order ends before it starts"));
+        }
+        if let FailurePolicy::Retry { max_attempts, interval_secs } = policy
+            && (max_attempts < 2 || !(1..=MAX_RETRY_INTERVAL_SECS).contains(&interval_secs))
+        {
+            return Err(LedgerError::validation(format!(
+                "Retry policy needs at least 2 attempts and an interval between 1 and {} seconds",
+                MAX_RETRY_INTERVAL_SECS
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn standing_order(&self, id: u64) -> Result<&StandingOrder, LedgerError> {
+        self.standing_orders.get(&id).ok_or(LedgerError::NotFound {
+            entity: "standing order",
+            id: id.to_string(),
+        })
+    }
+
+    /// Standing orders paying out of or into `account_id`.
+    pub fn standing_orders_for_account(&self, account_id: u32) -> Vec<&StandingOrder> {
+        self.standing_orders
+            .values()
+            .filter(|o| o.from == account_id || o.to == account_id)
+            .collect()
+    }
+
+    pub fn pause_standing_order(&mut self, id: u64) -> Result<(), LedgerError> {
+        self.set_standing_order_status(id, StandingOrderStatus::Active, StandingOrderStatus::Paused)
+    }
+
+    /// Resumes a paused order. Occurrences that fell due while it was paused
+    /// are not paid; the order picks up at the next one from now.
+    pub fn resume_standing_order(&mut self, id: u64, now: DateTime<Utc>) -> Result<(), LedgerError> {
+        self.set_standing_order_status(id, StandingOrderStatus::Paused, StandingOrderStatus::Active)?;
+        let order = self.standing_orders.get_mut(&id).expect("order checked above");
+        while order.status == StandingOrderStatus::Active && order.next_run < now {
+            order.advance();
+        }
+        Ok(())
+    }
+
+    pub fn cancel_standing_order(&mut self, id: u64) -> Result<(), LedgerError> {
+        let order = self.standing_order(id)?;
+        if matches!(order.status, StandingOrderStatus::Completed | StandingOrderStatus::Cancelled) {
+            return Err(LedgerError::validation(format!(
+                "Standing order {} is already {:?}",
+                id, order.status
+            )));
+        }
+        self.standing_orders.get_mut(&id).expect("order checked above").status = StandingOrderStatus::Cancelled;
+        Ok(())
+    }
+
+    fn set_standing_order_status(
+        &mut self,
+        id: u64,
+        from: StandingOrderStatus,
+        to: StandingOrderStatus,
+    ) -> Result<(), LedgerError> {
+        let order = self.standing_order(id)?;
+        if order.status != from {
+            return Err(LedgerError::validation(format!(
+                "Standing order {} is {:?}, expected {:?}",
+                id, order.status, from
+            )));
+        }
+        self.standing_orders.get_mut(&id).expect("order checked above").status = to;
+        Ok(())
+    }
+
+    /// Attempts every active order due at `now`, one occurrence per order,
+    /// and returns the execution records in order id order.
+    pub fn run_due_standing_orders(&mut self, now: DateTime<Utc>) -> Vec<StandingOrderExecution> {
+        let due: Vec<u64> = self
+            .standing_orders
+            .values()
+            .filter(|o| o.status == StandingOrderStatus::Active && o.next_run <= now)
+            .map(|o| o.id)
+            .collect();
+        due.into_iter().map(|id| self.execute_standing_order(id, now)).collect()
+    }
+
+    /// Pays the due occurrence of order `id`. The approval threshold is
+    /// checked again on every payment: one above it, e.g. after the threshold
+    /// was lowered, is queued for a checker unless the order was approved.
+    fn execute_standing_order(&mut self, id: u64, now: DateTime<Utc>) -> StandingOrderExecution {
+        let order = &self.standing_orders[&id];
+        let (from, to, amount, description) = (order.from, order.to, order.amount, order.description.clone());
+        let scheduled_for = order.occurrence_at(order.occurrence).unwrap_or(order.next_run);
+        let approval = order.approval.clone();
+        let transfer = Operation::Transfer { from, to, amount };
+        let result = if approval.is_none() && matches!(self.requires_approval(&transfer), Ok(true)) {
+            let maker = format!("standing order {}", id);
+            self.queue_for_approval(transfer, description, None, &maker).map(|approval_id| (None, Some(approval_id)))
+        } else {
+            let result = self.transfer(from, to, amount, description);
+            if let (Ok(_), Some(approval)) = (&result, approval) {
+                self.transactions.last_mut().expect("transaction just posted").approvals = vec![approval];
+            }
+            result.map(|tx_id| (Some(tx_id), None))
+        };
+
+        let order = self.standing_orders.get_mut(&id).expect("order checked above");
+        let (outcome, tx_id, approval_id, error) = match result {
+            Ok((tx_id, approval_id)) => {
+                order.advance();
+                let outcome = match approval_id {
+                    Some(_) => ExecutionOutcome::AwaitingApproval,
+                    None => ExecutionOutcome::Posted,
+                };
+                (outcome, tx_id, approval_id, None)
+            }
+            Err(e) => {
+                order.attempts += 1;
+                let retry_at = match (&e, order.policy) {
+                    (LedgerError::InsufficientFunds { .. }, FailurePolicy::Retry { max_attempts, interval_secs })
+                        if order.attempts < max_attempts =>
+                    {
+                        Duration::try_seconds(interval_secs).and_then(|interval| now.checked_add_signed(interval))
+                    }
+                    _ => None,
+                };
+                // A retry never runs into the next occurrence
+                let next_occurrence = order.occurrence_at(order.occurrence + 1);
+                match retry_at {
+                    Some(at) if next_occurrence.is_none_or(|next| at < next) => {
+                        order.next_run = at;
+                        (ExecutionOutcome::Retrying, None, None, Some(e.to_string()))
+                    }
+                    _ => {
+                        order.advance();
+                        (ExecutionOutcome::Skipped, None, None, Some(e.to_string()))
+                    }
+                }
+            }
+        };
+        let execution = StandingOrderExecution {
+            order_id: id,
+            from,
+            to,
+            amount,
+            scheduled_for,
+            executed_at: now,
+            outcome,
+            tx_id,
+            approval_id,
+            error,
+        };
+        order.executions.push(execution.clone());
+        execution
+    }
+}
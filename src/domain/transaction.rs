@@ -1,18 +1,122 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::api::dto::Kobo;
+use crate::domain::{currency::Currency, fx::FxConversion, money::Money};
 
-#[derive(Debug,Serialize,Deserialize,Clone)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Side {
+    Debit,
+    Credit,
+}
+
+/// One leg of a transaction: a strictly positive amount on one side of one account.
+#[derive(Debug,Serialize,Deserialize,Clone,PartialEq)]
+#[serde(from = "EntryRepr")]
 pub struct TransactionEntry {
     pub account_id: u32,
-    pub debit: Kobo,
-    pub credit: Kobo,
+    pub side: Side,
+    pub amount: Money,
+}
+
+/// Entries as written now, or as `debit`/`credit` integer pairs by snapshots
+/// taken before entries had a side.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EntryRepr {
+    Current { account_id: u32, side: Side, amount: Money },
+    Legacy { account_id: u32, debit: i64, credit: i64 },
+}
+
+impl From<EntryRepr> for TransactionEntry {
+    fn from(repr: EntryRepr) -> Self {
+        match repr {
+            EntryRepr::Current { account_id, side, amount } => TransactionEntry { account_id, side, amount },
+            // Those snapshots added debits to every balance, so a debit grew a
+            // customer deposit: it is a credit on today's liability accounts.
+            // The amount is read as naira and restated on load like balances.
+            EntryRepr::Legacy { account_id, debit, credit } if debit >= credit => {
+                TransactionEntry::credit(account_id, Money::new(debit - credit, Currency::NGN))
+            }
+            EntryRepr::Legacy { account_id, debit, credit } => {
+                TransactionEntry::debit(account_id, Money::new(credit - debit, Currency::NGN))
+            }
+        }
+    }
+}
+
+impl TransactionEntry {
+    pub fn debit(account_id: u32, amount: Money) -> Self {
+        TransactionEntry { account_id, side: Side::Debit, amount }
+    }
+
+    pub fn credit(account_id: u32, amount: Money) -> Self {
+        TransactionEntry { account_id, side: Side::Credit, amount }
+    }
+}
+/// What a charge levied on a customer is for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ChargeKind {
+    Fee,
+    /// Statutory levies, see [`crate::domain::levy::LevyKind`].
+    Emtl,
+    Vat,
+    Wht,
 }
+
+/// A charge collected as part of a transaction, on top of the principal.
+/// Its amount is added to the payer's debit leg, or taken off its credit leg.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Charge {
+    pub kind: ChargeKind,
+    pub payer: u32,
+    /// Account the charge is credited to.
+    pub credit_account: u32,
+    pub amount: Money,
+}
+
+/// Who approved a transaction before it posted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Approver {
+    /// A signatory of a joint account, by customer id.
+    Signatory(u64),
+    /// An operator checking a high-value operation made by another.
+    Operator(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Approval {
+    pub approver: Approver,
+    pub at: DateTime<Utc>,
+}
+
 #[derive(Debug,Clone,Serialize,Deserialize)]
 pub struct Transaction {
     pub id: u64,
     pub description: Option<String>,
     pub entries: Vec<TransactionEntry>,
     pub timestamp: DateTime<Utc>, // ISO-8601 string for skeleton
+    /// Rates applied when the transaction converts between currencies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx: Option<FxConversion>,
+    /// Id of the transaction this one reverses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverses: Option<u64>,
+    /// Id of the reversal posted against this transaction, once reversed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reversed_by: Option<u64>,
+    /// Fees and other charges collected by the transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub charges: Vec<Charge>,
+    /// Approvals the transaction needed before it could post, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<Approval>,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Debit => Side::Credit,
+            Side::Credit => Side::Debit,
+        }
+    }
 }
\ No newline at end of file
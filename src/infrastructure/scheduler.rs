@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use chrono::{Duration as Days, Utc};
+
+use crate::state::AppState;
+
+/// Background loop driving time-based ledger work. Runs forever; spawn it
+/// with `tokio::spawn`.
+pub async fn run(state: AppState, every: Duration) {
+    let mut ticker = tokio::time::interval(every);
+    loop {
+        ticker.tick().await;
+        tick(&state).await;
+    }
+}
+
+/// Executes the standing orders that are due, accrues interest for the last
+/// completed day and expires stale joint-account instructions, publishing an
+/// event per standing-order attempt, interest capitalization or accrual
+/// failure, and expiry.
+pub async fn tick(state: &AppState) {
+    let yesterday = (Utc::now() - Days::days(1)).date_naive();
+    let (executions, accrual, expired) = {
+        let mut ledger = state.ledger.write().await;
+        let executions = ledger.run_due_standing_orders(Utc::now());
+        let accrual = ledger.accrue_interest(yesterday);
+        let expired = ledger.expire_instructions(Utc::now());
+        let expired: Vec<_> = expired.iter().map(|id| ledger.instructions[id].clone()).collect();
+        (executions, accrual, expired)
+    };
+    for execution in executions {
+        let event = serde_json::json!({
+            "type": "standing_order",
+            "order_id": execution.order_id,
+            "from_id": execution.from,
+            "to_id": execution.to,
+            "amount": execution.amount.amount_minor,
+            "currency": execution.amount.currency,
+            "scheduled_for": execution.scheduled_for,
+            "outcome": execution.outcome,
+            "tx_id": execution.tx_id,
+            "approval_id": execution.approval_id,
+            "error": execution.error
+        });
+        let key = format!("{}->{}", execution.from, execution.to);
+        state.kafka.send("transactions", &key, &event.to_string()).await;
+    }
+    for capitalization in accrual.capitalizations {
+        let event = serde_json::json!({
+            "type": "interest_capitalized",
+            "account_id": capitalization.account_id,
+            "date": capitalization.date,
+            "amount": capitalization.amount.amount_minor,
+            "currency": capitalization.amount.currency,
+            "withheld": capitalization.withheld.amount_minor,
+            "tx_id": capitalization.tx_id
+        });
+        state.kafka.send("transactions", &capitalization.account_id.to_string(), &event.to_string()).await;
+    }
+    for failure in accrual.failures {
+        tracing::warn!(account_id = failure.account_id, date = %yesterday, "interest accrual failed: {}", failure.error);
+        let event = serde_json::json!({
+            "type": "interest_accrual_failed",
+            "account_id": failure.account_id,
+            "date": yesterday,
+            "error": failure.error
+        });
+        state.kafka.send("transactions", &failure.account_id.to_string(), &event.to_string()).await;
+    }
+    for instruction in expired {
+        let account_id = instruction.account_id;
+        let event = serde_json::json!({
+            "type": "instruction",
+            "instruction_id": instruction.id,
+            "account_id": account_id,
+            "operation": instruction.operation,
+            "status": instruction.status,
+            "approvals": instruction.approvals,
+            "tx_id": instruction.tx_id
+        });
+        state.kafka.send("transactions", &account_id.to_string(), &event.to_string()).await;
+    }
+}
@@ -2,7 +2,7 @@ pub mod api;
 pub mod domain;
 pub mod state;
 pub mod infrastructure;
-use api::routes::routes;
+use api::{auth::OperatorTokens, routes::routes};
 use state::AppState;
 use crate::infrastructure::kafka::KafkaProducer;
 use std::{net::SocketAddr, sync::Arc};
@@ -13,11 +13,29 @@ use tokio::sync::RwLock;
 async fn main() {
     tracing_subscriber::fmt::init();
     let kafka = KafkaProducer::new("localhost:9092"); // broker address
+    let mut ledger = domain::ledger::Ledger::new();
+    if let Ok(path) = std::env::var("FX_RATES_FILE") {
+        match domain::fx::RateTable::load_from_file(std::path::Path::new(&path)) {
+            Ok(table) => ledger.fx_rates = table,
+            Err(e) => eprintln!("Could not load FX rates from {}: {}", path, e),
+        }
+    }
+    let operators = OperatorTokens::from_env().unwrap_or_else(|e| {
+        eprintln!("Ignoring OPERATOR_TOKENS: {}", e);
+        OperatorTokens::default()
+    });
     let state = AppState {
-        ledger: Arc::new(RwLock::new(domain::ledger::Ledger::new())),
+        ledger: Arc::new(RwLock::new(ledger)),
         kafka,
+        operators: Arc::new(operators),
     };
 
+    let every = std::env::var("SCHEDULER_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60);
+    tokio::spawn(infrastructure::scheduler::run(state.clone(), std::time::Duration::from_secs(every)));
+
     let app = routes(state);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
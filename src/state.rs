@@ -1,5 +1,6 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use crate::api::auth::OperatorTokens;
 use crate::domain::ledger::Ledger;
 use crate::infrastructure::kafka::KafkaProducer;
 
@@ -8,4 +9,5 @@ use crate::infrastructure::kafka::KafkaProducer;
 pub struct AppState {
     pub ledger: Arc<RwLock<Ledger>>,
     pub kafka: KafkaProducer,
+    pub operators: Arc<OperatorTokens>,
 }
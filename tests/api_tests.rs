@@ -0,0 +1,118 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+use transaction_ledger::api::auth::OperatorTokens;
+use transaction_ledger::api::dto::{FxTransferResponse, SubmissionResponse, TxResponse};
+use transaction_ledger::domain::currency::Currency;
+use transaction_ledger::domain::error::LedgerError;
+use transaction_ledger::domain::fx::FxConversion;
+use transaction_ledger::domain::kyc::{KycTier, LimitKind};
+use transaction_ledger::domain::money::Money;
+use transaction_ledger::domain::transaction::Side;
+
+async fn problem(err: LedgerError) -> (StatusCode, String, serde_json::Value) {
+    let response = err.into_response();
+    let status = response.status();
+    let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn ledger_errors_render_as_problem_json() {
+    let (status, content_type, body) = problem(LedgerError::account_not_found(7)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(content_type, "application/problem+json");
+    assert_eq!(body["code"], "not_found");
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["type"], "urn:transaction-ledger:error:not_found");
+    assert_eq!(body["detail"], "account 7 not found");
+}
+
+#[tokio::test]
+async fn ledger_errors_map_to_expected_statuses() {
+    let cases = vec![
+        (LedgerError::Closed(1), StatusCode::CONFLICT),
+        (
+            LedgerError::Restricted { account_id: 1, status: "frozen".into(), side: Side::Debit },
+            StatusCode::CONFLICT,
+        ),
+        (
+            LedgerError::InsufficientFunds { account_id: 1, available: 0, requested: 10 },
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            LedgerError::LimitExceeded {
+                account_id: 1,
+                tier: KycTier::Tier1,
+                limit: LimitKind::DailyDebit,
+                allowed: 5_000_000,
+                attempted: 5_000_001,
+            },
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            LedgerError::Unbalanced { currency: Currency::NGN, debits: 1, credits: 2 },
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            LedgerError::CurrencyMismatch { expected: Currency::NGN, found: Currency::USD },
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (LedgerError::IdempotencyKeyReused("k".into()), StatusCode::UNPROCESSABLE_ENTITY),
+        (LedgerError::validation("bad"), StatusCode::BAD_REQUEST),
+        (LedgerError::Unauthorized("who".into()), StatusCode::UNAUTHORIZED),
+        (LedgerError::Persistence("disk".into()), StatusCode::INTERNAL_SERVER_ERROR),
+    ];
+    for (err, expected) in cases {
+        let code = err.code();
+        let (status, _, body) = problem(err).await;
+        assert_eq!(status, expected);
+        assert_eq!(body["code"], code);
+    }
+}
+
+#[test]
+fn replayed_fx_transfers_keep_their_conversion() {
+    let conversion = FxConversion {
+        from: Currency::USD,
+        to: Currency::NGN,
+        mid_rate: 1_500.0,
+        applied_rate: 1_485.0,
+        source_amount: Money::new(10_000, Currency::USD),
+        target_amount: Money::new(14_850_000, Currency::NGN),
+        spread_income: Money::new(150_000, Currency::NGN),
+    };
+    let stored = SubmissionResponse::Converted(FxTransferResponse { tx_id: 7, conversion: conversion.clone() });
+    match serde_json::from_value(serde_json::to_value(stored).unwrap()).unwrap() {
+        SubmissionResponse::Converted(replayed) => assert_eq!((replayed.tx_id, replayed.conversion), (7, conversion)),
+        other => panic!("expected an FX transfer, got {:?}", other),
+    }
+    let posted: SubmissionResponse = serde_json::from_value(serde_json::json!({ "tx_id": 7 })).unwrap();
+    assert!(matches!(posted, SubmissionResponse::Posted(TxResponse { tx_id: 7 })));
+}
+
+#[test]
+fn operators_are_identified_by_their_bearer_token() {
+    let operators = OperatorTokens::parse("ada:s3cret, musa:0th3r").unwrap();
+    let headers = |pairs: &[(&'static str, &str)]| {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    };
+    let unauthorized = |result: Result<Option<String>, LedgerError>| matches!(result, Err(LedgerError::Unauthorized(_)));
+
+    assert_eq!(operators.authenticate(&headers(&[])).unwrap(), None);
+    assert_eq!(operators.authenticate(&headers(&[("authorization", "Bearer 0th3r")])).unwrap().as_deref(), Some("musa"));
+    let both = headers(&[("authorization", "Bearer s3cret"), ("x-operator-id", "ada")]);
+    assert_eq!(operators.authenticate(&both).unwrap().as_deref(), Some("ada"));
+
+    assert!(unauthorized(operators.authenticate(&headers(&[("x-operator-id", "ada")]))), "the header alone is not trusted");
+    assert!(unauthorized(operators.authenticate(&headers(&[("authorization", "Bearer guess")]))));
+    let impersonating = headers(&[("authorization", "Bearer 0th3r"), ("x-operator-id", "ada")]);
+    assert!(unauthorized(operators.authenticate(&impersonating)));
+
+    assert!(OperatorTokens::parse("ada").is_err());
+    assert!(OperatorTokens::parse("ada:same,musa:same").is_err(), "tokens must be unique");
+}
@@ -17,6 +17,7 @@ pub async fn setup_app() -> Router {
     let state = AppState {
         ledger: Arc::new(RwLock::new(Ledger::new())),
         kafka,
+        operators: Default::default(),
     };
 
     routes(state)
@@ -1,16 +1,93 @@
-use std::sync::Arc;
-
-use axum::body::Body;
-use axum::http::{Request, StatusCode};
-//use hyper::Body;
-use serde_json::json;
-use transaction_ledger::api::dto::CreateAccountResponse;
+use transaction_ledger::domain::account::{AccountStatus, AccountType, FreezeScope, SystemRole};
+use transaction_ledger::domain::approval::ApprovalStatus;
 use transaction_ledger::domain::currency::Currency;
-use transaction_ledger::domain::ledger::{Ledger};
-use tower::ServiceExt; // 👈 Import this for oneshot
+use transaction_ledger::domain::customer::CustomerDetails;
+use transaction_ledger::domain::error::LedgerError;
+use transaction_ledger::domain::fee::{FeeOperation, FeeSchedule, FeeTier};
+use transaction_ledger::domain::fx::{FxConversion, FxRate};
+use transaction_ledger::domain::hold::HoldStatus;
+use transaction_ledger::domain::idempotency::fingerprint;
+use transaction_ledger::domain::interest::{DayCount, InterestTerms};
+use transaction_ledger::domain::kyc::{KycTier, LimitKind};
+use transaction_ledger::domain::ledger::{Ledger, LedgerSettings, OpeningOptions};
+use transaction_ledger::domain::money::Money;
+use transaction_ledger::domain::product::Product;
+use transaction_ledger::domain::standing_order::{ExecutionOutcome, FailurePolicy, Frequency, StandingOrderStatus};
+use transaction_ledger::domain::levy::LevyKind;
+use transaction_ledger::domain::lien::LienStatus;
+use transaction_ledger::domain::mandate::{InstructionStatus, MandateRule, Operation, Submission};
+use transaction_ledger::domain::transaction::{Approver, ChargeKind, Side, TransactionEntry};
 
 mod common;
 
+fn ngn(amount: i64) -> Money {
+    Money::new(amount, Currency::NGN)
+}
+
+fn usd(amount: i64) -> Money {
+    Money::new(amount, Currency::USD)
+}
+
+fn balance(ledger: &Ledger, id: u32) -> i64 {
+    ledger.get_balance(id).unwrap().amount_minor
+}
+
+/// Debit-normal balances must equal credit-normal balances across the whole ledger.
+fn assert_books_balance(ledger: &Ledger) {
+    let net: i64 = ledger
+        .accounts
+        .values()
+        .map(|a| match a.account_type.normal_balance() {
+            Side::Debit => a.balance.amount_minor,
+            Side::Credit => -a.balance.amount_minor,
+        })
+        .sum();
+    assert_eq!(net, 0);
+}
+
+fn ledger_with_account(initial: i64) -> (Ledger, u32) {
+    let mut ledger = Ledger::new();
+    let id = ledger
+        .create_account("Alice".into(), ngn(initial), "First Bank".into(), "011".into())
+        .unwrap();
+    (ledger, id)
+}
+
+#[test]
+fn withdraw_from_unknown_account_is_not_found() {
+    let mut ledger = Ledger::new();
+    let err = ledger.withdraw(42, ngn(100), None).unwrap_err();
+    assert_eq!(err, LedgerError::account_not_found(42));
+    assert_eq!(err.code(), "not_found");
+}
+
+#[test]
+fn withdraw_more_than_balance_is_insufficient_funds() {
+    let (mut ledger, id) = ledger_with_account(1_000);
+    let err = ledger.withdraw(id, ngn(5_000), None).unwrap_err();
+    assert_eq!(
+        err,
+        LedgerError::InsufficientFunds { account_id: id, available: 1_000, requested: 5_000 }
+    );
+    assert_eq!(err.code(), "insufficient_funds");
+}
+
+#[test]
+fn invalid_bank_code_is_a_validation_error() {
+    let mut ledger = Ledger::new();
+    let err = ledger
+        .create_account("Bob".into(), Money::new(0, Currency::NGN), "Bank".into(), "12".into())
+        .unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+}
+
+#[test]
+fn closing_funded_account_is_rejected() {
+    let (mut ledger, id) = ledger_with_account(500);
+    let err = ledger.close_account(id, "Customer request").unwrap_err();
+    assert_eq!(err, LedgerError::BalanceNotZero { account_id: id, balance: 500 });
+}
+
 //#[tokio::test]
 /*async fn test_create_account() {
     let app = common::setup_app().await;
@@ -270,3 +347,1668 @@ async fn test_withdraw_reduces_balance() {
 
     assert_eq!(balance["balance"].as_i64().unwrap(), 3000); // 5000 - 2000
 }*/
+
+#[test]
+fn each_currency_has_its_own_settlement_account() {
+    let mut ledger = Ledger::new();
+    let usd = ledger
+        .create_account("Carol".into(), Money::new(0, Currency::USD), "First Bank".into(), "011".into())
+        .unwrap();
+    ledger.deposit(usd, self::usd(2_500), None).unwrap();
+
+    let usd_settlement = ledger.settlement_account(Currency::USD).unwrap();
+    let ngn_settlement = ledger.settlement_account(Currency::NGN).unwrap();
+    assert_eq!(balance(&ledger, usd_settlement), 2_500);
+    assert_eq!(balance(&ledger, ngn_settlement), 0);
+}
+
+#[test]
+fn mixed_currency_transaction_is_rejected() {
+    let (mut ledger, ngn) = ledger_with_account(1_000);
+    let usd = ledger
+        .create_account("Dave".into(), Money::new(0, Currency::USD), "First Bank".into(), "011".into())
+        .unwrap();
+    let entries = vec![
+        TransactionEntry::debit(usd, self::usd(100)),
+        TransactionEntry::credit(ngn, self::ngn(100)),
+    ];
+    let err = ledger.record_transaction(None, entries).unwrap_err();
+    assert_eq!(err, LedgerError::CurrencyMismatch { expected: Currency::NGN, found: Currency::USD });
+
+    let err = ledger.transfer(ngn, usd, self::ngn(100), None).unwrap_err();
+    assert_eq!(err.code(), "currency_mismatch");
+    assert_eq!(balance(&ledger, ngn), 1_000);
+}
+
+#[test]
+fn conversion_must_balance_per_currency() {
+    let (mut ledger, ngn) = ledger_with_account(1_000);
+    let usd = ledger
+        .create_account("Erin".into(), Money::new(0, Currency::USD), "First Bank".into(), "011".into())
+        .unwrap();
+    let entries = vec![
+        TransactionEntry::debit(usd, self::usd(100)),
+        TransactionEntry::credit(ngn, self::ngn(100)),
+    ];
+    let conversion = FxConversion {
+        from: Currency::NGN,
+        to: Currency::USD,
+        mid_rate: 1.0,
+        applied_rate: 1.0,
+        source_amount: self::ngn(100),
+        target_amount: self::usd(100),
+        spread_income: self::usd(0),
+    };
+    let err = ledger.record_conversion(None, entries, conversion).unwrap_err();
+    assert_eq!(err, LedgerError::Unbalanced { currency: Currency::NGN, debits: 0, credits: 100 });
+}
+
+fn usd_ngn_rate(mid: f64, spread_bps: u32) -> FxRate {
+    FxRate { base: Currency::USD, quote: Currency::NGN, mid, spread_bps, updated_at: chrono::Utc::now() }
+}
+
+#[test]
+fn fx_transfer_books_spread_as_income() {
+    let mut ledger = Ledger::new();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 200)).unwrap();
+    let usd = ledger
+        .create_account("Fola".into(), Money::new(10_000, Currency::USD), "First Bank".into(), "011".into())
+        .unwrap();
+    let ngn = ledger
+        .create_account("Gbenga".into(), Money::new(0, Currency::NGN), "First Bank".into(), "011".into())
+        .unwrap();
+
+    // ₦148,500 is above tier 1 limits
+    ledger.upgrade_kyc_tier(ngn, KycTier::Tier3, "ops").unwrap();
+    let tx_id = ledger.fx_transfer(usd, ngn, self::usd(10_000), None).unwrap();
+
+    // 100.00 USD at 1500 less 1% (half the spread) = 148,500.00 NGN
+    assert_eq!(balance(&ledger, usd), 0);
+    assert_eq!(balance(&ledger, ngn), 14_850_000);
+    let income = ledger.system_account(SystemRole::FxIncome, Currency::NGN).unwrap();
+    assert_eq!(balance(&ledger, income), 150_000);
+    let tx = ledger.transactions.iter().find(|t| t.id == tx_id).unwrap();
+    let fx = tx.fx.as_ref().unwrap();
+    assert_eq!(fx.mid_rate, 1_500.0);
+    assert_eq!(fx.applied_rate, 1_485.0);
+}
+
+#[test]
+fn fx_transfer_uses_inverse_rate_and_requires_a_quote() {
+    let mut ledger = Ledger::new();
+    let ngn = ledger
+        .create_account("Hauwa".into(), Money::new(1_500_000, Currency::NGN), "First Bank".into(), "011".into())
+        .unwrap();
+    let usd = ledger
+        .create_account("Ike".into(), Money::new(0, Currency::USD), "First Bank".into(), "011".into())
+        .unwrap();
+    assert_eq!(ledger.fx_transfer(ngn, usd, self::ngn(1_500_000), None).unwrap_err().code(), "not_found");
+
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 0)).unwrap();
+    ledger.fx_transfer(ngn, usd, self::ngn(1_500_000), None).unwrap();
+    assert_eq!(balance(&ledger, usd), 1_000);
+}
+
+#[test]
+fn fx_conversion_is_exact_beyond_float_precision() {
+    let mut ledger = Ledger::new();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_000.0, 0)).unwrap();
+    // 2^53 + 1 has no exact f64 representation
+    let quote = ledger.fx_quote(usd(9_007_199_254_740_993), Currency::NGN).unwrap();
+    assert_eq!(quote.target_amount, ngn(9_007_199_254_740_993_000));
+    let back = ledger.fx_quote(ngn(9_007_199_254_740_993_000), Currency::USD).unwrap();
+    assert_eq!(back.target_amount, usd(9_007_199_254_740_993));
+    assert!(ledger.fx_quote(usd(i64::MAX), Currency::NGN).is_err(), "overflow is an error, not a wrapped amount");
+}
+
+#[test]
+fn money_parses_decimal_strings_per_currency_exponent() {
+    assert_eq!(Money::parse_decimal("1250.50", Currency::NGN).unwrap(), ngn(125_050));
+    assert_eq!(Money::parse_decimal("1250.5", Currency::NGN).unwrap(), ngn(125_050));
+    assert_eq!(Money::parse_decimal("-3", Currency::USD).unwrap(), usd(-300));
+    assert!(Money::parse_decimal("1.234", Currency::NGN).is_err());
+    assert!(Money::parse_decimal("12a", Currency::NGN).is_err());
+    assert_eq!(ngn(125_050).to_string(), "₦1250.50");
+    assert_eq!(usd(-5).to_string(), "-$0.05");
+}
+
+#[test]
+fn money_refuses_mixed_currency_arithmetic() {
+    let err = ngn(100).checked_add(usd(100)).unwrap_err();
+    assert_eq!(err.code(), "currency_mismatch");
+    assert_eq!(ngn(100).checked_sub(ngn(40)).unwrap(), ngn(60));
+    assert_eq!(ngn(i64::MAX).checked_add(ngn(1)).unwrap_err().code(), "overflow");
+}
+
+#[test]
+fn money_deserializes_from_minor_units_or_decimal_strings() {
+    let minor: Money = serde_json::from_str(r#"{"amount_minor": 125050, "currency": "NGN"}"#).unwrap();
+    let decimal: Money = serde_json::from_str(r#"{"amount": "1250.50", "currency": "NGN"}"#).unwrap();
+    assert_eq!(minor, decimal);
+    assert_eq!(
+        serde_json::to_value(minor).unwrap(),
+        serde_json::json!({"amount_minor": 125050, "currency": "NGN"})
+    );
+}
+
+#[test]
+fn snapshots_from_before_money_and_sides_load_with_their_balances() {
+    // Baseline format: integer balances, debit/credit entries and a bank
+    // account whose balance went negative as customers deposited.
+    let account = |id: u32, owner: &str, balance: i64| {
+        serde_json::json!({
+            "id": id, "owner": owner, "balance": balance, "closed": false, "currency": "NGN",
+            "bankName": "First Bank", "bankCode": "011", "accountNumber": format!("{:011}", id)
+        })
+    };
+    let snapshot = serde_json::json!({
+        "accounts": { "0": account(0, "BANK", -10_000), "1": account(1, "Ada", 7_000), "2": account(2, "Musa", 3_000) },
+        "transactions": [
+            { "id": 1, "description": "Deposit", "timestamp": "2025-01-01T00:00:00Z",
+              "entries": [{ "account_id": 1, "debit": 10_000, "credit": 0 }, { "account_id": 0, "debit": 0, "credit": 10_000 }] },
+            { "id": 2, "description": null, "timestamp": "2025-01-02T00:00:00Z",
+              "entries": [{ "account_id": 1, "debit": 0, "credit": 3_000 }, { "account_id": 2, "debit": 3_000, "credit": 0 }] }
+        ],
+        "next_account_id": 3,
+        "next_tx_id": 3,
+        "bank_account_id": 0
+    });
+    let mut ledger: Ledger = serde_json::from_value(snapshot).unwrap();
+    ledger.after_load().unwrap();
+
+    assert_eq!((balance(&ledger, 0), balance(&ledger, 1), balance(&ledger, 2)), (10_000, 7_000, 3_000));
+    assert_eq!(ledger.system_account(SystemRole::Settlement, Currency::NGN), Some(0));
+    assert!(ledger.system_account(SystemRole::Settlement, Currency::USD).is_some());
+    let deposit = &ledger.transactions[0].entries;
+    assert_eq!((deposit[0].side, deposit[0].amount), (Side::Credit, ngn(10_000)));
+    assert_eq!((deposit[1].side, deposit[1].amount), (Side::Debit, ngn(10_000)));
+    assert_books_balance(&ledger);
+
+    ledger.withdraw(1, ngn(2_000), None).unwrap();
+    assert_eq!((balance(&ledger, 0), balance(&ledger, 1)), (8_000, 5_000));
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn legacy_initial_balances_are_posted_against_equity_on_load() {
+    // Baseline accounts opened with an initial balance had no transaction for it.
+    let snapshot = serde_json::json!({
+        "accounts": {
+            "0": { "id": 0, "owner": "BANK", "balance": 0, "closed": false, "currency": "NGN",
+                   "bankName": "First Bank", "bankCode": "011", "accountNumber": "00000000000" },
+            "1": { "id": 1, "owner": "Ada", "balance": 5_000, "closed": false, "currency": "NGN",
+                   "bankName": "First Bank", "bankCode": "011", "accountNumber": "00000000001" }
+        },
+        "transactions": [],
+        "next_account_id": 2,
+        "next_tx_id": 1,
+        "bank_account_id": 0
+    });
+    let mut ledger: Ledger = serde_json::from_value(snapshot).unwrap();
+    ledger.after_load().unwrap();
+
+    let equity = ledger.system_account(SystemRole::OpeningBalance, Currency::NGN).unwrap();
+    assert_eq!((balance(&ledger, 1), balance(&ledger, equity)), (5_000, -5_000));
+    assert_eq!(ledger.balance_as_of(1, chrono::Utc::now()).unwrap(), ngn(5_000));
+    assert_eq!(ledger.balance_as_of(equity, chrono::Utc::now()).unwrap(), ngn(-5_000));
+    let txs = ledger.transactions_for_account(1);
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].description.as_deref(), Some("Opening balance (migrated)"));
+    assert_books_balance(&ledger);
+
+    ledger.withdraw(1, ngn(2_000), None).unwrap();
+    assert_eq!(ledger.balance_as_of(1, chrono::Utc::now()).unwrap(), ngn(3_000));
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn opening_balance_is_posted_against_equity() {
+    let (ledger, id) = ledger_with_account(5_000);
+    let equity = ledger.system_account(SystemRole::OpeningBalance, Currency::NGN).unwrap();
+
+    assert_eq!(balance(&ledger, id), 5_000);
+    assert_eq!(balance(&ledger, equity), -5_000);
+    let txs = ledger.transactions_for_account(id);
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].description.as_deref(), Some("Opening balance"));
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn opening_balance_rejected_by_funding_account_leaves_no_account() {
+    let mut ledger = Ledger::new();
+    let equity = ledger.ensure_system_account(SystemRole::OpeningBalance, Currency::NGN).unwrap();
+    ledger.close_account(equity, "Retired").unwrap();
+    let accounts_before = ledger.accounts.len();
+
+    let err = ledger
+        .create_account("Jide".into(), ngn(1_000), "First Bank".into(), "011".into())
+        .unwrap_err();
+    assert_eq!(err, LedgerError::Closed(equity));
+    assert_eq!(ledger.accounts.len(), accounts_before);
+    assert!(ledger.transactions.is_empty());
+}
+
+#[test]
+fn opening_balance_account_is_configurable() {
+    let mut ledger = Ledger::new();
+    let funding = ledger
+        .create_account("Treasury".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    ledger.set_system_account(SystemRole::OpeningBalance, Currency::NGN, funding).unwrap();
+    let id = ledger
+        .create_account("Kemi".into(), ngn(700), "First Bank".into(), "011".into())
+        .unwrap();
+    assert_eq!(balance(&ledger, funding), -700);
+    assert_eq!(balance(&ledger, id), 700);
+
+    let usd = ledger
+        .create_account("Lola".into(), usd(0), "First Bank".into(), "011".into())
+        .unwrap();
+    let err = ledger.set_system_account(SystemRole::OpeningBalance, Currency::NGN, usd).unwrap_err();
+    assert_eq!(err.code(), "currency_mismatch");
+}
+
+fn two_accounts() -> (Ledger, u32, u32) {
+    let (mut ledger, a) = ledger_with_account(1_000);
+    let b = ledger
+        .create_account("Musa".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    (ledger, a, b)
+}
+
+fn entry(account_id: u32, side: Side, amount: i64) -> TransactionEntry {
+    TransactionEntry { account_id, side, amount: ngn(amount) }
+}
+
+#[test]
+fn entries_with_zero_amount_are_rejected() {
+    let (mut ledger, a, b) = two_accounts();
+    let err = ledger
+        .record_transaction(None, vec![entry(a, Side::Debit, 0), entry(b, Side::Credit, 0)])
+        .unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+}
+
+#[test]
+fn entries_with_negative_amount_are_rejected() {
+    let (mut ledger, a, b) = two_accounts();
+    let err = ledger
+        .record_transaction(None, vec![entry(a, Side::Debit, -100), entry(b, Side::Credit, -100)])
+        .unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+    assert_eq!(balance(&ledger, a), 1_000);
+}
+
+#[test]
+fn duplicate_accounts_in_one_transaction_are_rejected() {
+    let (mut ledger, a, b) = two_accounts();
+    let entries = vec![entry(a, Side::Debit, 50), entry(a, Side::Debit, 50), entry(b, Side::Credit, 100)];
+    let err = ledger.record_transaction(None, entries).unwrap_err();
+    assert_eq!(err, LedgerError::validation(format!("Account {} appears more than once in the transaction", a)));
+}
+
+#[test]
+fn single_entry_transactions_are_rejected() {
+    let (mut ledger, a, _) = two_accounts();
+    let err = ledger.record_transaction(None, vec![entry(a, Side::Debit, 100)]).unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+}
+
+#[test]
+fn transactions_over_the_entry_limit_are_rejected() {
+    let (mut ledger, a, b) = two_accounts();
+    let c = ledger
+        .create_account("Ngozi".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    ledger.settings.max_entries_per_transaction = 2;
+    let entries = vec![entry(a, Side::Debit, 100), entry(b, Side::Credit, 60), entry(c, Side::Credit, 40)];
+    let err = ledger.record_transaction(None, entries.clone()).unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+
+    ledger.settings.max_entries_per_transaction = 3;
+    ledger.record_transaction(None, entries).unwrap();
+    assert_eq!(balance(&ledger, c), 40);
+}
+
+#[test]
+fn unbalanced_entries_are_rejected() {
+    let (mut ledger, a, b) = two_accounts();
+    let err = ledger
+        .record_transaction(None, vec![entry(a, Side::Debit, 100), entry(b, Side::Credit, 90)])
+        .unwrap_err();
+    assert_eq!(err, LedgerError::Unbalanced { currency: Currency::NGN, debits: 100, credits: 90 });
+}
+
+#[test]
+fn deposits_must_be_positive() {
+    let (mut ledger, a) = ledger_with_account(0);
+    assert_eq!(ledger.deposit(a, ngn(0), None).unwrap_err().code(), "validation_error");
+    assert_eq!(ledger.deposit(a, ngn(-500), None).unwrap_err().code(), "validation_error");
+    assert_eq!(balance(&ledger, a), 0);
+}
+
+#[test]
+fn customer_deposits_are_liabilities_backed_by_settlement_assets() {
+    let (mut ledger, a) = ledger_with_account(0);
+    ledger.deposit(a, ngn(10_000), None).unwrap();
+    ledger.withdraw(a, ngn(2_500), None).unwrap();
+
+    let account = &ledger.accounts[&a];
+    assert_eq!(account.account_type, AccountType::Liability);
+    assert_eq!(account.balance, ngn(7_500));
+    let settlement = ledger.settlement_account(Currency::NGN).unwrap();
+    assert_eq!(ledger.accounts[&settlement].account_type, AccountType::Asset);
+    assert_eq!(ledger.total_assets().unwrap()[0], ngn(7_500));
+    assert_eq!(ledger.total_by_type(AccountType::Liability).unwrap(), vec![ngn(7_500)]);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn fx_spread_is_income() {
+    let mut ledger = Ledger::new();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_000.0, 200)).unwrap();
+    let from = ledger
+        .create_account("Oye".into(), usd(100), "First Bank".into(), "011".into())
+        .unwrap();
+    let to = ledger
+        .create_account("Pat".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    ledger.fx_transfer(from, to, usd(100), None).unwrap();
+    assert_eq!(ledger.total_by_type(AccountType::Income).unwrap(), vec![ngn(1_000)]);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn rollup_aggregates_subtree_per_currency() {
+    let mut ledger = Ledger::new();
+    let deposits = ledger
+        .create_group_account("Customer Deposits".into(), AccountType::Liability, Currency::NGN, None)
+        .unwrap();
+    let savings = ledger
+        .create_group_account("Savings".into(), AccountType::Liability, Currency::NGN, Some(deposits))
+        .unwrap();
+    let current = ledger
+        .create_group_account("Current".into(), AccountType::Liability, Currency::NGN, Some(deposits))
+        .unwrap();
+    for (owner, amount, parent) in [("Ada", ngn(1_000), savings), ("Bayo", usd(300), savings), ("Chi", ngn(250), current)] {
+        let id = ledger
+            .create_account(owner.into(), amount, "First Bank".into(), "011".into())
+            .unwrap();
+        ledger.set_parent(id, Some(parent)).unwrap();
+    }
+
+    let root = ledger.rollup(deposits).unwrap();
+    assert_eq!(root.balances, vec![ngn(1_250), usd(300)]);
+    assert_eq!(root.children.len(), 2);
+    assert_eq!(ledger.rollup(savings).unwrap().balances, vec![ngn(1_000), usd(300)]);
+    assert_eq!(ledger.rollup(current).unwrap().balances, vec![ngn(250)]);
+}
+
+#[test]
+fn group_accounts_cannot_be_posted_to_or_form_cycles() {
+    let (mut ledger, a) = ledger_with_account(500);
+    let root = ledger
+        .create_group_account("Deposits".into(), AccountType::Liability, Currency::NGN, None)
+        .unwrap();
+    let child = ledger
+        .create_group_account("Savings".into(), AccountType::Liability, Currency::NGN, Some(root))
+        .unwrap();
+    let err = ledger
+        .record_transaction(None, vec![entry(root, Side::Credit, 100), entry(a, Side::Debit, 100)])
+        .unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+
+    assert!(ledger.set_parent(root, Some(child)).is_err());
+    assert!(ledger.set_parent(child, Some(a)).is_err(), "leaf accounts cannot be parents");
+    let income = ledger
+        .create_group_account("Income".into(), AccountType::Income, Currency::NGN, None)
+        .unwrap();
+    assert!(ledger.set_parent(a, Some(income)).is_err(), "parent type must match");
+}
+
+#[test]
+fn reversal_mirrors_the_original_and_links_both() {
+    let (mut ledger, a, b) = two_accounts();
+    let tx = ledger.transfer(a, b, ngn(400), None).unwrap();
+    let reversal = ledger.reverse_transaction(tx, "Wrong beneficiary").unwrap();
+
+    assert_eq!(balance(&ledger, a), 1_000);
+    assert_eq!(balance(&ledger, b), 0);
+    assert_eq!(ledger.transaction(tx).unwrap().reversed_by, Some(reversal));
+    let mirror = ledger.transaction(reversal).unwrap();
+    assert_eq!(mirror.reverses, Some(tx));
+    assert!(mirror.entries.iter().any(|e| e.account_id == a && e.side == Side::Credit));
+    assert_books_balance(&ledger);
+
+    assert_eq!(ledger.reverse_transaction(tx, "again").unwrap_err().code(), "validation_error");
+    assert_eq!(ledger.reverse_transaction(reversal, "undo").unwrap_err().code(), "validation_error");
+    assert_eq!(ledger.reverse_transaction(999, "missing").unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn reversal_refuses_to_overdraw_or_touch_closed_accounts() {
+    let (mut ledger, a, b) = two_accounts();
+    let tx = ledger.transfer(a, b, ngn(400), None).unwrap();
+    ledger.withdraw(b, ngn(300), None).unwrap();
+    let err = ledger.reverse_transaction(tx, "Fraud").unwrap_err();
+    assert_eq!(err, LedgerError::InsufficientFunds { account_id: b, available: 100, requested: 400 });
+
+    let deposit = ledger.deposit(b, ngn(50), None).unwrap();
+    ledger.withdraw(b, ngn(150), None).unwrap();
+    ledger.close_account(b, "Customer request").unwrap();
+    assert_eq!(ledger.reverse_transaction(deposit, "Bounced").unwrap_err(), LedgerError::Closed(b));
+    assert_eq!(ledger.transaction(deposit).unwrap().reversed_by, None);
+}
+
+#[test]
+fn fx_transfers_can_be_reversed() {
+    let mut ledger = Ledger::new();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 200)).unwrap();
+    let from = ledger
+        .create_account("Fola".into(), usd(10_000), "First Bank".into(), "011".into())
+        .unwrap();
+    let to = ledger
+        .create_account("Gbenga".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    // ₦148,500 is above tier 1 limits
+    ledger.upgrade_kyc_tier(to, KycTier::Tier3, "ops").unwrap();
+    let tx = ledger.fx_transfer(from, to, usd(10_000), None).unwrap();
+    ledger.reverse_transaction(tx, "Customer cancelled").unwrap();
+    assert_eq!(balance(&ledger, from), 10_000);
+    assert_eq!(balance(&ledger, to), 0);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn holds_reduce_available_balance_until_released() {
+    let (mut ledger, a, b) = two_accounts();
+    let hold = ledger.authorize(a, ngn(700), Some("Card auth".into()), None).unwrap();
+    assert_eq!(balance(&ledger, a), 1_000);
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(300));
+
+    let err = ledger.transfer(a, b, ngn(400), None).unwrap_err();
+    assert_eq!(err, LedgerError::InsufficientFunds { account_id: a, available: 300, requested: 400 });
+    assert!(ledger.withdraw(a, ngn(301), None).is_err());
+    assert!(ledger.authorize(a, ngn(301), None, None).is_err());
+
+    ledger.release(hold).unwrap();
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(1_000));
+    assert_eq!(ledger.release(hold).unwrap_err().code(), "validation_error");
+    ledger.transfer(a, b, ngn(400), None).unwrap();
+}
+
+#[test]
+fn partial_capture_posts_and_frees_the_remainder() {
+    let (mut ledger, a) = ledger_with_account(1_000);
+    let hold = ledger.authorize(a, ngn(600), None, None).unwrap();
+    assert!(ledger.capture(hold, Some(ngn(601)), None).is_err());
+
+    let tx = ledger.capture(hold, Some(ngn(450)), Some("Fuel".into())).unwrap();
+    assert_eq!(balance(&ledger, a), 550);
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(550));
+    let captured = &ledger.holds[&hold];
+    assert_eq!(captured.status, HoldStatus::Captured);
+    assert_eq!((captured.captured, captured.capture_tx_id), (Some(ngn(450)), Some(tx)));
+    assert!(ledger.capture(hold, None, None).is_err());
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn expired_holds_stop_reserving_funds() {
+    let (mut ledger, a) = ledger_with_account(1_000);
+    let hold = ledger.authorize(a, ngn(1_000), None, Some(chrono::Duration::seconds(60))).unwrap();
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(0));
+
+    assert_eq!(ledger.expire_holds(chrono::Utc::now() + chrono::Duration::seconds(61)), 1);
+    assert_eq!(ledger.holds[&hold].status, HoldStatus::Expired);
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(1_000));
+    assert!(ledger.capture(hold, None, None).is_err());
+    assert_eq!(ledger.holds_for_account(a).len(), 1);
+
+    let too_far = ledger.authorize(a, ngn(100), None, Some(chrono::Duration::MAX)).unwrap_err();
+    assert_eq!(too_far.code(), "validation_error");
+    let settings = LedgerSettings { hold_ttl_secs: i64::MAX / 1_000 - 1, ..ledger.settings.clone() };
+    assert!(matches!(settings.validate(), Err(LedgerError::Validation(_))));
+    assert_eq!(ledger.holds_for_account(a).len(), 1);
+}
+
+#[tokio::test]
+async fn holds_survive_a_snapshot_round_trip() {
+    let (mut ledger, a) = ledger_with_account(1_000);
+    let hold = ledger.authorize(a, ngn(250), None, None).unwrap();
+    let path = std::env::temp_dir().join(format!("ledger-holds-{}.json", std::process::id()));
+    ledger.save_to_file(&path).await.unwrap();
+    let mut restored = Ledger::load_from_file(&path).await.unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(restored.holds[&hold], ledger.holds[&hold]);
+    assert_eq!(restored.available_balance(a).unwrap(), ngn(750));
+    assert_eq!(restored.authorize(a, ngn(1), None, None).unwrap(), hold + 1);
+}
+
+#[test]
+fn overdraft_limit_sets_the_floor_for_every_posting_path() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.set_overdraft_limit(a, ngn(500)).unwrap();
+    assert_eq!(ledger.spendable_balance(a).unwrap(), ngn(1_500));
+
+    ledger.transfer(a, b, ngn(1_200), None).unwrap();
+    assert_eq!(balance(&ledger, a), -200);
+    let err = ledger.withdraw(a, ngn(301), None).unwrap_err();
+    assert_eq!(err, LedgerError::InsufficientFunds { account_id: a, available: 300, requested: 301 });
+
+    // Hand-built postings go through the same floor
+    let settlement = ledger.system_account(SystemRole::Settlement, Currency::NGN).unwrap();
+    let err = ledger
+        .record_transaction(None, vec![entry(a, Side::Debit, 301), entry(settlement, Side::Credit, 301)])
+        .unwrap_err();
+    assert_eq!(err.code(), "insufficient_funds");
+    let err = ledger
+        .record_transaction(None, vec![entry(b, Side::Debit, 1_201), entry(settlement, Side::Credit, 1_201)])
+        .unwrap_err();
+    assert_eq!(err.code(), "insufficient_funds");
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn lowering_an_overdraft_only_blocks_further_debits() {
+    let (mut ledger, a) = ledger_with_account(1_000);
+    ledger.set_overdraft_limit(a, ngn(500)).unwrap();
+    ledger.withdraw(a, ngn(1_400), None).unwrap();
+    ledger.set_overdraft_limit(a, ngn(0)).unwrap();
+
+    assert!(ledger.withdraw(a, ngn(1), None).is_err());
+    ledger.deposit(a, ngn(100), None).unwrap();
+    assert_eq!(balance(&ledger, a), -300);
+    assert!(ledger.set_overdraft_limit(a, ngn(-1)).is_err());
+    assert!(ledger.set_overdraft_limit(a, usd(100)).is_err());
+    let settlement = ledger.system_account(SystemRole::Settlement, Currency::NGN).unwrap();
+    assert!(ledger.set_overdraft_limit(settlement, ngn(100)).is_err());
+}
+
+#[test]
+fn idempotency_keys_replay_matching_requests_only() {
+    let (mut ledger, _) = ledger_with_account(0);
+    let request = serde_json::json!({ "id": 4, "amount": 500, "description": null });
+    let reordered = serde_json::json!({ "description": null, "amount": 500, "id": 4 });
+    let fp = fingerprint("deposit", &request).unwrap();
+    assert_eq!(fp, fingerprint("deposit", &reordered).unwrap());
+    assert_ne!(fp, fingerprint("withdraw", &request).unwrap());
+
+    assert_eq!(ledger.idempotent_response("retry-1", &fp).unwrap(), None);
+    ledger.remember_response("retry-1".into(), fp.clone(), serde_json::json!({ "tx_id": 2 }));
+    assert_eq!(ledger.idempotent_response("retry-1", &fp).unwrap(), Some(serde_json::json!({ "tx_id": 2 })));
+
+    let other = fingerprint("deposit", &serde_json::json!({ "id": 4, "amount": 900 })).unwrap();
+    let err = ledger.idempotent_response("retry-1", &other).unwrap_err();
+    assert_eq!(err, LedgerError::IdempotencyKeyReused("retry-1".into()));
+}
+
+#[tokio::test]
+async fn idempotency_keys_persist_and_expire() {
+    let (mut ledger, _) = ledger_with_account(0);
+    ledger.settings.idempotency_retention_secs = 60;
+    ledger.remember_response("k".into(), "deposit {}".into(), serde_json::json!({ "tx_id": 1 }));
+    let path = std::env::temp_dir().join(format!("ledger-idem-{}.json", std::process::id()));
+    ledger.save_to_file(&path).await.unwrap();
+    let mut restored = Ledger::load_from_file(&path).await.unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert!(restored.idempotent_response("k", "deposit {}").unwrap().is_some());
+    assert_eq!(restored.purge_idempotency_keys(chrono::Utc::now() + chrono::Duration::seconds(61)), 1);
+    assert_eq!(restored.idempotent_response("k", "deposit {}").unwrap(), None);
+}
+
+fn at(date: &str) -> chrono::DateTime<chrono::Utc> {
+    format!("{}T09:00:00Z", date).parse().unwrap()
+}
+
+#[test]
+fn monthly_standing_orders_follow_the_end_of_month() {
+    let (mut ledger, a, b) = two_accounts();
+    let id = ledger
+        .create_standing_order(a, b, ngn(100), Some("Rent".into()), Frequency::Monthly, at("2025-01-31"), None, FailurePolicy::Skip)
+        .unwrap();
+
+    assert!(ledger.run_due_standing_orders(at("2025-01-30")).is_empty());
+    for (now, expected_next) in [("2025-01-31", "2025-02-28"), ("2025-02-28", "2025-03-31"), ("2025-03-31", "2025-04-30")] {
+        let runs = ledger.run_due_standing_orders(at(now));
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].outcome, ExecutionOutcome::Posted);
+        assert_eq!(ledger.standing_orders[&id].next_run, at(expected_next));
+    }
+    assert_eq!(balance(&ledger, b), 300);
+    assert_eq!(ledger.standing_orders[&id].executions.len(), 3);
+}
+
+#[test]
+fn one_off_orders_complete_and_can_be_paused_or_cancelled() {
+    let (mut ledger, a, b) = two_accounts();
+    let once = ledger
+        .create_standing_order(a, b, ngn(50), None, Frequency::Once, at("2025-06-01"), None, FailurePolicy::Skip)
+        .unwrap();
+    let daily = ledger
+        .create_standing_order(a, b, ngn(10), None, Frequency::Daily, at("2025-06-01"), Some(at("2025-06-30")), FailurePolicy::Skip)
+        .unwrap();
+    ledger.pause_standing_order(daily).unwrap();
+
+    assert_eq!(ledger.run_due_standing_orders(at("2025-06-01")).len(), 1);
+    assert_eq!(ledger.standing_orders[&once].status, StandingOrderStatus::Completed);
+    assert_eq!(balance(&ledger, b), 50);
+
+    ledger.resume_standing_order(daily, at("2025-06-10")).unwrap();
+    assert_eq!(ledger.standing_orders[&daily].next_run, at("2025-06-10"));
+    ledger.cancel_standing_order(daily).unwrap();
+    assert!(ledger.run_due_standing_orders(at("2025-06-20")).is_empty());
+    assert!(ledger.cancel_standing_order(once).is_err());
+}
+
+#[test]
+fn insufficient_funds_are_retried_then_skipped() {
+    let (mut ledger, a, b) = two_accounts();
+    let retry = FailurePolicy::Retry { max_attempts: 2, interval_secs: 3_600 };
+    let id = ledger
+        .create_standing_order(b, a, ngn(200), None, Frequency::Weekly, at("2025-03-03"), None, retry)
+        .unwrap();
+
+    let runs = ledger.run_due_standing_orders(at("2025-03-03"));
+    assert_eq!(runs[0].outcome, ExecutionOutcome::Retrying);
+    assert_eq!(ledger.standing_orders[&id].next_run, at("2025-03-03") + chrono::Duration::hours(1));
+
+    let runs = ledger.run_due_standing_orders(at("2025-03-03") + chrono::Duration::hours(1));
+    assert_eq!(runs[0].outcome, ExecutionOutcome::Skipped);
+    assert_eq!(runs[0].scheduled_for, at("2025-03-03"));
+    assert_eq!(ledger.standing_orders[&id].next_run, at("2025-03-10"));
+
+    ledger.deposit(b, ngn(200), None).unwrap();
+    let runs = ledger.run_due_standing_orders(at("2025-03-10"));
+    assert_eq!(runs[0].outcome, ExecutionOutcome::Posted);
+    assert_eq!(balance(&ledger, a), 1_200);
+}
+
+#[test]
+fn retry_intervals_out_of_range_are_rejected_or_skipped() {
+    let (mut ledger, a, b) = two_accounts();
+    let huge = FailurePolicy::Retry { max_attempts: 2, interval_secs: i64::MAX };
+    let order = |ledger: &mut Ledger, policy| {
+        ledger.create_standing_order(b, a, ngn(200), None, Frequency::Weekly, at("2025-03-03"), None, policy)
+    };
+    assert!(matches!(order(&mut ledger, huge), Err(LedgerError::Validation(_))));
+
+    // An order from an older snapshot may still carry one; its retry is skipped instead
+    let id = order(&mut ledger, FailurePolicy::Retry { max_attempts: 2, interval_secs: 3_600 }).unwrap();
+    ledger.standing_orders.get_mut(&id).unwrap().policy = huge;
+    let runs = ledger.run_due_standing_orders(at("2025-03-03"));
+    assert_eq!(runs[0].outcome, ExecutionOutcome::Skipped);
+    assert_eq!(ledger.standing_orders[&id].next_run, at("2025-03-10"));
+}
+
+#[tokio::test]
+async fn balance_as_of_replays_history_and_survives_reload() {
+    let (mut ledger, a, b) = two_accounts();
+    let opened = ledger.transactions.last().unwrap().timestamp;
+    assert_eq!(ledger.balance_as_of(a, opened - chrono::Duration::seconds(1)).unwrap(), ngn(0));
+    assert_eq!(ledger.balance_as_of(a, opened).unwrap(), ngn(1_000));
+
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let tx = ledger.transfer(a, b, ngn(300), None).unwrap();
+    let moved = ledger.transaction(tx).unwrap().timestamp;
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    ledger.withdraw(a, ngn(200), None).unwrap();
+
+    let between = moved + chrono::Duration::microseconds(1);
+    assert_eq!(ledger.balance_as_of(a, between).unwrap(), ngn(700));
+    assert_eq!(ledger.balance_as_of(b, between).unwrap(), ngn(300));
+    assert_eq!(ledger.balance_as_of(a, chrono::Utc::now()).unwrap(), ngn(500));
+
+    let path = std::env::temp_dir().join(format!("ledger-as-of-{}.json", std::process::id()));
+    ledger.save_to_file(&path).await.unwrap();
+    let restored = Ledger::load_from_file(&path).await.unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(restored.balance_as_of(a, between).unwrap(), ngn(700));
+    assert_eq!(restored.balance_as_of(99, between).unwrap_err(), LedgerError::account_not_found(99));
+}
+
+#[test]
+fn trial_balance_totals_each_currency_over_the_period() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger
+        .create_account("Fola".into(), usd(5_000), "First Bank".into(), "011".into())
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let from = chrono::Utc::now();
+    ledger.transfer(a, b, ngn(300), None).unwrap();
+    ledger.deposit(b, ngn(50), None).unwrap();
+
+    let report = ledger.trial_balance(Some(from), None).unwrap();
+    assert!(report.balanced);
+    let naira = report.currencies.iter().find(|c| c.currency == Currency::NGN).unwrap();
+    assert_eq!((naira.total_debits, naira.total_credits), (ngn(350), ngn(350)));
+    let line = |id| naira.lines.iter().find(|l| l.account_id == id).unwrap();
+    assert_eq!((line(a).debits, line(a).credits), (ngn(300), ngn(0)));
+    assert_eq!((line(b).debits, line(b).credits), (ngn(0), ngn(350)));
+    let dollars = report.currencies.iter().find(|c| c.currency == Currency::USD).unwrap();
+    assert_eq!(dollars.total_debits, usd(0), "USD opening balance falls before the period");
+
+    let everything = ledger.trial_balance(None, None).unwrap();
+    let dollars = everything.currencies.iter().find(|c| c.currency == Currency::USD).unwrap();
+    assert_eq!((dollars.total_debits, dollars.total_credits), (usd(5_000), usd(5_000)));
+    assert!(ledger.trial_balance(Some(from), Some(from - chrono::Duration::seconds(1))).is_err());
+}
+
+#[test]
+fn trial_balance_exports_csv() {
+    let mut ledger = Ledger::new();
+    let id = ledger
+        .create_account("Okafor, Ada".into(), ngn(12_050), "First Bank".into(), "011".into())
+        .unwrap();
+    let csv = ledger.trial_balance(None, None).unwrap().to_csv();
+    let mut rows = csv.lines();
+    assert_eq!(rows.next(), Some("currency,account_id,owner,account_type,debits,credits"));
+    assert!(csv.contains(&format!("NGN,{},\"Okafor, Ada\",Liability,0.00,120.50\n", id)));
+    assert!(csv.contains("NGN,,TOTAL,,120.50,120.50\n"));
+}
+
+#[test]
+fn balance_sheet_balances_with_current_earnings() {
+    let mut ledger = Ledger::new();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 200)).unwrap();
+    let from = ledger
+        .create_account("Fola".into(), usd(10_000), "First Bank".into(), "011".into())
+        .unwrap();
+    let to = ledger
+        .create_account("Gbenga".into(), ngn(0), "First Bank".into(), "011".into())
+        .unwrap();
+    ledger.deposit(to, ngn(2_000), None).unwrap();
+    // ₦148,500 is above tier 1 limits
+    ledger.upgrade_kyc_tier(to, KycTier::Tier3, "ops").unwrap();
+    ledger.fx_transfer(from, to, usd(10_000), None).unwrap();
+
+    let sheet = ledger.balance_sheet(chrono::Utc::now(), None).unwrap();
+    assert!(sheet.currencies.iter().all(|c| c.balanced));
+    let naira = sheet.currencies.iter().find(|c| c.currency == Currency::NGN).unwrap();
+    assert_eq!(naira.assets.total, ngn(2_000));
+    assert!(naira.current_earnings.is_positive(), "FX spread is income");
+    assert_eq!(naira.liabilities.lines.iter().find(|l| l.account_id == to).unwrap().amount.amount_minor, balance(&ledger, to));
+
+    let sheet = ledger.balance_sheet(chrono::Utc::now(), Some(Currency::NGN)).unwrap();
+    let converted = sheet.converted.unwrap();
+    assert_eq!(converted.total_assets, ngn(2_000));
+    assert_eq!(
+        converted.total_assets,
+        converted.total_liabilities.checked_add(converted.total_equity).unwrap()
+    );
+    assert!(ledger.balance_sheet(chrono::Utc::now(), Some(Currency::EUR)).is_err(), "no EUR rates");
+}
+
+#[test]
+fn income_statement_reports_period_activity() {
+    let (mut ledger, a, _) = two_accounts();
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 0)).unwrap();
+    ledger.deposit(a, ngn(300_000), None).unwrap();
+    assert!(ledger.income_statement(None, None, None).unwrap().currencies.is_empty());
+
+    let income = ledger.ensure_system_account(SystemRole::FxIncome, Currency::NGN).unwrap();
+    let charge = |ledger: &mut Ledger| {
+        ledger
+            .record_transaction(None, vec![entry(a, Side::Debit, 150_000), entry(income, Side::Credit, 150_000)])
+            .unwrap()
+    };
+    charge(&mut ledger);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let from = chrono::Utc::now();
+    charge(&mut ledger);
+
+    let statement = ledger.income_statement(Some(from), None, Some(Currency::USD)).unwrap();
+    assert_eq!(statement.currencies.len(), 1);
+    assert_eq!(statement.currencies[0].income.total, ngn(150_000));
+    assert_eq!(statement.currencies[0].net_income, ngn(150_000));
+    assert_eq!(statement.converted.unwrap().net_income, usd(100));
+    let all_time = ledger.income_statement(None, None, None).unwrap();
+    assert_eq!(all_time.currencies[0].income.total, ngn(300_000));
+}
+
+fn day(date: &str) -> chrono::NaiveDate {
+    date.parse().unwrap()
+}
+
+#[test]
+fn day_count_conventions() {
+    assert_eq!(DayCount::Act365.year_fraction(day("2025-01-31"), day("2025-02-01")), 1.0 / 365.0);
+    assert_eq!(DayCount::Thirty360.year_fraction(day("2025-01-30"), day("2025-01-31")), 0.0);
+    assert_eq!(DayCount::Thirty360.year_fraction(day("2025-01-31"), day("2025-02-01")), 1.0 / 360.0);
+    assert_eq!(DayCount::Thirty360.year_fraction(day("2025-02-28"), day("2025-03-01")), 3.0 / 360.0);
+    assert_eq!(DayCount::Thirty360.days(day("2025-02-28"), day("2025-03-01")), (3, 360));
+}
+
+#[test]
+fn interest_accrues_daily_and_capitalizes_at_month_end() {
+    let (mut ledger, a) = ledger_with_account(3_650_000);
+    ledger
+        .upsert_product(Product {
+            code: "SAV".into(),
+            name: "Savings".into(),
+            interest: Some(InterestTerms { annual_rate_bps: 1_000, day_count: DayCount::Act365 }),
+        })
+        .unwrap();
+    ledger.assign_product(a, Some("SAV".into())).unwrap();
+
+    // 10% on 36,500.00 under ACT/365 is 10.00 a day. Accrual reads historical
+    // balances, so the schedule has to start from the day the account was funded.
+    let today = chrono::Utc::now().date_naive();
+    assert!(ledger.accrue_interest(today).capitalizations.is_empty());
+    assert_eq!(ledger.interest_accruals[&a].accrued(), ngn(1_000));
+    assert!(ledger.accrue_interest(today).capitalizations.is_empty(), "re-run is a no-op");
+    assert_eq!(ledger.interest_accruals[&a].accrued(), ngn(1_000));
+
+    let next_month = chrono::Datelike::with_day(&today, 1).unwrap().checked_add_months(chrono::Months::new(1)).unwrap();
+    let month_end = next_month.pred_opt().unwrap();
+    let days = (month_end - today).num_days() + 1;
+    let run = ledger.accrue_interest(next_month);
+    assert!(run.failures.is_empty());
+    let capitalized = run.capitalizations;
+    assert_eq!(capitalized.len(), 1);
+    assert_eq!((capitalized[0].date, capitalized[0].amount), (month_end, ngn(1_000 * days)));
+    // 10% withholding tax is deducted from the interest paid in
+    assert_eq!(capitalized[0].withheld, ngn(100 * days));
+    assert_eq!(balance(&ledger, a), 3_650_000 + 900 * days);
+    let wht = ledger.system_account(SystemRole::WhtPayable, Currency::NGN).unwrap();
+    assert_eq!(balance(&ledger, wht), 100 * days);
+    assert_eq!(ledger.interest_accruals[&a].accrued_through, next_month);
+    assert_eq!(ledger.interest_accruals[&a].last_capitalized, Some(month_end));
+    let expense = ledger.system_account(SystemRole::InterestExpense, Currency::NGN).unwrap();
+    assert_eq!(balance(&ledger, expense), 1_000 * days);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn interest_accrual_failures_skip_only_the_failing_account() {
+    let (mut ledger, a) = ledger_with_account(3_650_000);
+    let dollars = ledger.create_account("Fola".into(), usd(10_000), "First Bank".into(), "011".into()).unwrap();
+    let terms = InterestTerms { annual_rate_bps: 1_000, day_count: DayCount::Act365 };
+    for id in [a, dollars] {
+        ledger.set_account_interest(id, Some(terms)).unwrap();
+    }
+    // Dollar interest has nowhere to be paid from
+    let expense = ledger.ensure_system_account(SystemRole::InterestExpense, Currency::USD).unwrap();
+    ledger.close_account(expense, "Retired").unwrap();
+
+    let today = chrono::Utc::now().date_naive();
+    let next_month = chrono::Datelike::with_day(&today, 1).unwrap().checked_add_months(chrono::Months::new(1)).unwrap();
+    let month_end = next_month.pred_opt().unwrap();
+    ledger.accrue_interest(today);
+    let run = ledger.accrue_interest(next_month);
+    assert_eq!(run.capitalizations.iter().map(|c| c.account_id).collect::<Vec<_>>(), vec![a]);
+    assert_eq!(run.failures.len(), 1);
+    assert_eq!(run.failures[0].account_id, dollars);
+    assert_eq!(ledger.interest_accruals[&a].accrued_through, next_month);
+    // The failing account stops before the month end and retries it on the next run
+    assert_eq!(ledger.interest_accruals[&dollars].accrued_through, month_end.pred_opt().unwrap());
+    assert_eq!(ledger.accrue_interest(next_month).failures.len(), 1);
+    assert_eq!(balance(&ledger, dollars), 10_000);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn account_interest_overrides_the_product() {
+    let (mut ledger, a) = ledger_with_account(100);
+    assert_eq!(ledger.interest_terms(a).unwrap(), None);
+    let terms = InterestTerms { annual_rate_bps: 250, day_count: DayCount::Thirty360 };
+    ledger.set_account_interest(a, Some(terms)).unwrap();
+    assert_eq!(ledger.interest_terms(a).unwrap(), Some(terms));
+    assert!(ledger.set_account_interest(a, Some(InterestTerms { annual_rate_bps: 10_001, ..terms })).is_err());
+    assert_eq!(ledger.assign_product(a, Some("NOPE".into())).unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn fee_schedules_price_amounts() {
+    let (mut ledger, a) = ledger_with_account(0);
+    let tiered = FeeSchedule::Tiered {
+        tiers: vec![
+            FeeTier { up_to: Some(500_000), flat: 1_000, bps: 0 },
+            FeeTier { up_to: Some(5_000_000), flat: 2_500, bps: 0 },
+            FeeTier { up_to: None, flat: 0, bps: 10 },
+        ],
+    };
+    ledger.add_fee_rule(FeeOperation::Transfer, None, Currency::NGN, tiered, None, Some(100_000)).unwrap();
+    ledger.add_fee_rule(FeeOperation::Withdrawal, None, Currency::NGN, FeeSchedule::Percentage { bps: 50 }, Some(500), None).unwrap();
+
+    let quote = |op, amount| ledger.quote_fee(op, a, ngn(amount)).unwrap().fee.amount_minor;
+    assert_eq!(quote(FeeOperation::Transfer, 500_000), 1_000);
+    assert_eq!(quote(FeeOperation::Transfer, 500_001), 2_500);
+    assert_eq!(quote(FeeOperation::Transfer, 50_000_000), 50_000);
+    assert_eq!(quote(FeeOperation::Transfer, 500_000_000), 100_000, "capped");
+    assert_eq!(quote(FeeOperation::Withdrawal, 10_000), 500, "minimum");
+    assert_eq!(quote(FeeOperation::Withdrawal, 1_000_000), 5_000);
+
+    let bad = FeeSchedule::Tiered { tiers: vec![FeeTier { up_to: None, flat: 0, bps: 0 }, FeeTier { up_to: Some(1), flat: 0, bps: 0 }] };
+    assert!(ledger.add_fee_rule(FeeOperation::Withdrawal, None, Currency::USD, bad, None, None).is_err());
+    let over_whole = FeeSchedule::Percentage { bps: 10_001 };
+    assert!(ledger.add_fee_rule(FeeOperation::Withdrawal, None, Currency::USD, over_whole, None, None).is_err());
+    let over_whole = FeeSchedule::Tiered { tiers: vec![FeeTier { up_to: None, flat: 0, bps: u32::MAX }] };
+    assert!(ledger.add_fee_rule(FeeOperation::Withdrawal, None, Currency::USD, over_whole, None, None).is_err());
+    let duplicate = ledger.add_fee_rule(FeeOperation::Transfer, None, Currency::NGN, FeeSchedule::Flat { amount: 1 }, None, None);
+    assert!(duplicate.is_err());
+}
+
+#[test]
+fn transfer_posts_fee_leg_in_the_same_transaction() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.upsert_product(Product { code: "PREMIUM".into(), name: "Premium".into(), interest: None }).unwrap();
+    ledger.add_fee_rule(FeeOperation::Transfer, None, Currency::NGN, FeeSchedule::Flat { amount: 50 }, None, None).unwrap();
+    ledger.add_fee_rule(FeeOperation::Transfer, Some("PREMIUM".into()), Currency::NGN, FeeSchedule::Flat { amount: 0 }, None, None).unwrap();
+
+    let tx_id = ledger.transfer(a, b, ngn(400), None).unwrap();
+    let tx = ledger.transaction(tx_id).unwrap();
+    // Principal, fee and 7.5% VAT on the fee (3.75, rounded up) in one transaction
+    assert_eq!(tx.entries.len(), 4);
+    assert_eq!(tx.charges.len(), 2);
+    assert_eq!((tx.charges[0].kind, tx.charges[0].amount), (ChargeKind::Fee, ngn(50)));
+    assert_eq!((tx.charges[1].kind, tx.charges[1].amount), (ChargeKind::Vat, ngn(4)));
+    let income = ledger.system_account(SystemRole::FeeIncome, Currency::NGN).unwrap();
+    assert_eq!(tx.charges[0].credit_account, income);
+    assert_eq!((balance(&ledger, a), balance(&ledger, b), balance(&ledger, income)), (546, 400, 50));
+    assert_books_balance(&ledger);
+
+    // Charges count against the floor: 546 covers 500 but not 500 plus charges
+    let err = ledger.transfer(a, b, ngn(500), None).unwrap_err();
+    assert!(matches!(err, LedgerError::InsufficientFunds { requested: 554, .. }));
+    assert_eq!(balance(&ledger, a), 546);
+
+    // Premium accounts are covered by their own zero-fee rule
+    ledger.assign_product(a, Some("PREMIUM".into())).unwrap();
+    let tx_id = ledger.transfer(a, b, ngn(546), None).unwrap();
+    assert!(ledger.transaction(tx_id).unwrap().charges.is_empty());
+    assert_eq!(balance(&ledger, a), 0);
+}
+
+#[test]
+fn emtl_is_levied_on_qualifying_inbound_credits() {
+    let (mut ledger, a, b) = two_accounts();
+    let emtl = |ledger: &Ledger| ledger.system_account(SystemRole::EmtlPayable, Currency::NGN).map(|id| balance(ledger, id));
+
+    ledger.deposit(b, ngn(999_999), None).unwrap();
+    assert_eq!(emtl(&ledger), None, "below the ₦10,000 threshold");
+
+    let tx_id = ledger.deposit(b, ngn(1_000_000), None).unwrap();
+    let tx = ledger.transaction(tx_id).unwrap();
+    assert_eq!((tx.charges[0].kind, tx.charges[0].payer, tx.charges[0].amount), (ChargeKind::Emtl, b, ngn(5_000)));
+    assert_eq!(balance(&ledger, b), 999_999 + 995_000);
+    assert_eq!(emtl(&ledger), Some(5_000));
+
+    // Transfers levy the recipient, not the sender
+    ledger.deposit(a, ngn(2_000_000), None).unwrap();
+    ledger.transfer(a, b, ngn(1_500_000), None).unwrap();
+    assert_eq!(balance(&ledger, a), 1_000 + 1_995_000 - 1_500_000);
+    assert_eq!(emtl(&ledger), Some(15_000));
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn levy_rules_take_effect_by_date() {
+    let mut ledger = Ledger::new();
+    let on = |ledger: &Ledger, date: &str| ledger.levy_rule_on(LevyKind::Emtl, Currency::NGN, day(date)).map(|r| r.levy_for(2_000_000).unwrap());
+    assert_eq!(on(&ledger, "2019-12-31"), None);
+    assert_eq!(on(&ledger, "2024-06-01"), Some(5_000));
+
+    ledger.add_levy_rule(LevyKind::Emtl, Currency::NGN, day("2030-01-01"), 2_000_000, 10_000, 0).unwrap();
+    assert_eq!(on(&ledger, "2029-12-31"), Some(5_000));
+    assert_eq!(on(&ledger, "2030-01-01"), Some(10_000));
+    assert!(ledger.add_levy_rule(LevyKind::Emtl, Currency::NGN, day("2030-01-01"), 0, 1, 0).is_err());
+    assert!(ledger.add_levy_rule(LevyKind::Vat, Currency::NGN, day("2030-01-01"), 0, 0, 10_001).is_err());
+}
+
+#[test]
+fn levy_remittance_nets_reversed_charges() {
+    let (mut ledger, _a, b) = two_accounts();
+    let from = chrono::Utc::now();
+    ledger.deposit(b, ngn(1_000_000), None).unwrap();
+    let reversed = ledger.deposit(b, ngn(2_000_000), None).unwrap();
+    ledger.reverse_transaction(reversed, "duplicate").unwrap();
+
+    let report = ledger.levy_remittance(Some(from), None).unwrap();
+    assert_eq!(report.lines.len(), 1);
+    let line = &report.lines[0];
+    assert_eq!((line.kind, line.charges), (LevyKind::Emtl, 2));
+    assert_eq!((line.collected, line.refunded, line.net, line.outstanding), (ngn(10_000), ngn(5_000), ngn(5_000), ngn(5_000)));
+    assert!(report.to_csv().starts_with("levy,currency,payable_account,charges,collected,refunded,net,outstanding\nEmtl,NGN,"));
+}
+
+#[test]
+fn freezes_block_postings_by_direction() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.freeze_account(a, FreezeScope::Debit, "Court order").unwrap();
+    let err = ledger.transfer(a, b, ngn(100), None).unwrap_err();
+    assert_eq!(err, LedgerError::Restricted { account_id: a, status: "frozen for debits".into(), side: Side::Debit });
+    assert_eq!(err.code(), "account_restricted");
+    ledger.deposit(a, ngn(100), None).unwrap();
+    assert!(ledger.authorize(a, ngn(100), None, None).is_err(), "holds are debits too");
+
+    ledger.freeze_account(a, FreezeScope::Credit, "Investigation").unwrap();
+    assert!(ledger.deposit(a, ngn(100), None).is_err());
+    ledger.withdraw(a, ngn(100), None).unwrap();
+
+    ledger.freeze_account(a, FreezeScope::Total, "Investigation").unwrap();
+    assert!(ledger.deposit(a, ngn(1), None).is_err());
+    assert!(ledger.withdraw(a, ngn(1), None).is_err());
+    assert!(ledger.close_account(a, "Customer request").is_err(), "unfreeze before closing");
+
+    ledger.unfreeze_account(a, "Cleared").unwrap();
+    ledger.transfer(a, b, ngn(100), None).unwrap();
+    assert_eq!(balance(&ledger, a), 900);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn status_transitions_are_validated_and_recorded() {
+    let (mut ledger, a) = ledger_with_account(0);
+    assert!(ledger.unfreeze_account(a, "Nothing to lift").is_err());
+    assert!(ledger.reopen_account(a, "Already open").is_err());
+    assert!(ledger.mark_dormant(a, " ").is_err(), "a reason is required");
+
+    ledger.mark_pending(a, "Awaiting documents").unwrap();
+    ledger.deposit(a, ngn(500), None).unwrap();
+    assert!(matches!(ledger.withdraw(a, ngn(1), None), Err(LedgerError::Restricted { .. })));
+    ledger.activate_account(a, "Documents received").unwrap();
+    assert!(ledger.mark_pending(a, "Again").is_err());
+
+    ledger.withdraw(a, ngn(500), None).unwrap();
+    ledger.close_account(a, "Customer request").unwrap();
+    assert_eq!(ledger.deposit(a, ngn(1), None).unwrap_err(), LedgerError::Closed(a));
+    ledger.reopen_account(a, "Opened in error").unwrap();
+
+    let history: Vec<_> = ledger.accounts[&a].status_history.iter().map(|c| (c.from, c.to)).collect();
+    assert_eq!(history, vec![
+        (AccountStatus::Active, AccountStatus::Pending),
+        (AccountStatus::Pending, AccountStatus::Active),
+        (AccountStatus::Active, AccountStatus::Closed),
+        (AccountStatus::Closed, AccountStatus::Active),
+    ]);
+    assert_eq!(ledger.accounts[&a].status_history[2].reason, "Customer request");
+}
+
+#[test]
+fn closing_requires_holds_liens_and_standing_orders_to_be_cleared() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.transfer(a, b, ngn(1_000), None).unwrap();
+    ledger.set_overdraft_limit(a, ngn(500)).unwrap();
+    let hold = ledger.authorize(a, ngn(200), None, None).unwrap();
+    let lien = ledger.place_lien(a, ngn(100), "Garnishee order".into(), "FHC/L/CS/2/2025".into(), None).unwrap();
+    let order = ledger
+        .create_standing_order(b, a, ngn(10), None, Frequency::Monthly, chrono::Utc::now(), None, FailurePolicy::Skip)
+        .unwrap();
+
+    assert!(ledger.close_account(a, "Customer request").is_err(), "active hold");
+    ledger.release(hold).unwrap();
+    assert!(ledger.close_account(a, "Customer request").is_err(), "active lien");
+    ledger.lift_lien(a, lien).unwrap();
+    assert!(ledger.close_account(a, "Customer request").is_err(), "standing order");
+    ledger.pause_standing_order(order).unwrap();
+    assert!(ledger.close_account(a, "Customer request").is_err(), "paused standing order");
+    ledger.cancel_standing_order(order).unwrap();
+    ledger.close_account(a, "Customer request").unwrap();
+}
+
+#[test]
+fn snapshots_with_a_closed_flag_load_as_closed() {
+    let (mut ledger, a) = ledger_with_account(0);
+    let mut snapshot = serde_json::to_value(&ledger).unwrap();
+    let account = &mut snapshot["accounts"][a.to_string()];
+    account.as_object_mut().unwrap().remove("status");
+    account["closed"] = serde_json::json!(true);
+    ledger = serde_json::from_value(snapshot).unwrap();
+    ledger.after_load().unwrap();
+    assert_eq!(ledger.accounts[&a].status, AccountStatus::Closed);
+}
+
+#[test]
+fn liens_block_funds_on_every_posting_path() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.set_overdraft_limit(a, ngn(5_000)).unwrap();
+    let lien = ledger.place_lien(a, ngn(700), "Garnishee order".into(), "FHC/L/CS/1/2025".into(), None).unwrap();
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(300));
+    assert_eq!(ledger.spendable_balance(a).unwrap(), ngn(300), "the overdraft does not cover liened funds");
+
+    assert!(matches!(ledger.transfer(a, b, ngn(301), None), Err(LedgerError::InsufficientFunds { .. })));
+    assert!(ledger.withdraw(a, ngn(301), None).is_err());
+    assert!(ledger.authorize(a, ngn(301), None, None).is_err());
+    let journal = vec![entry(a, Side::Debit, 301), entry(b, Side::Credit, 301)];
+    assert!(ledger.record_transaction(None, journal).is_err());
+    ledger.transfer(a, b, ngn(300), None).unwrap();
+
+    ledger.amend_lien(a, lien, Some(ngn(500)), None, None).unwrap();
+    ledger.transfer(a, b, ngn(200), None).unwrap();
+    ledger.lift_lien(a, lien).unwrap();
+    assert_eq!(ledger.spendable_balance(a).unwrap(), ngn(5_500));
+    assert!(ledger.lift_lien(a, lien).is_err(), "already lifted");
+}
+
+#[test]
+fn enforcing_a_lien_debits_it_to_recovery() {
+    let (mut ledger, a, _b) = two_accounts();
+    let lien = ledger.place_lien(a, ngn(600), "Tax recovery".into(), "FIRS/2025/88".into(), None).unwrap();
+    let tx_id = ledger.enforce_lien(a, lien).unwrap();
+    let recovery = ledger.system_account(SystemRole::LienRecovery, Currency::NGN).unwrap();
+    assert_eq!((balance(&ledger, a), balance(&ledger, recovery)), (400, 600));
+    let enforced = ledger.lien(a, lien).unwrap();
+    assert_eq!((enforced.status, enforced.enforce_tx_id), (LienStatus::Enforced, Some(tx_id)));
+    assert_eq!(ledger.accounts[&a].active_liens().count(), 0);
+    assert_books_balance(&ledger);
+
+    // A lien the balance cannot cover stays in force when enforcement fails
+    let short = ledger.place_lien(a, ngn(900), "Court order".into(), "CS/2".into(), None).unwrap();
+    assert!(ledger.enforce_lien(a, short).is_err());
+    assert_eq!(ledger.lien(a, short).unwrap().status, LienStatus::Active);
+
+    // Nor can the overdraft be drawn on to enforce it
+    ledger.set_overdraft_limit(a, ngn(1_000)).unwrap();
+    assert!(matches!(ledger.enforce_lien(a, short), Err(LedgerError::InsufficientFunds { available: 400, .. })));
+    assert_eq!((balance(&ledger, a), ledger.lien(a, short).unwrap().status), (400, LienStatus::Active));
+}
+
+fn limit_hit(result: Result<u64, LedgerError>) -> LimitKind {
+    match result {
+        Err(LedgerError::LimitExceeded { limit, .. }) => limit,
+        other => panic!("expected a limit error, got {:?}", other),
+    }
+}
+
+#[test]
+fn tier_one_limits_apply_to_deposits_withdrawals_and_transfers() {
+    let (mut ledger, a) = ledger_with_account(0);
+    let b = ledger.create_account("Musa".into(), ngn(0), "GTBank".into(), "058".into()).unwrap();
+    assert_eq!(ledger.accounts[&a].kyc_tier, KycTier::Tier1);
+
+    assert_eq!(limit_hit(ledger.deposit(a, ngn(5_000_001), None)), LimitKind::SingleTransaction);
+    let deposits: Vec<u64> = (0..6).map(|_| ledger.deposit(a, ngn(5_000_000), None).unwrap()).collect();
+    assert_eq!(balance(&ledger, a), 29_970_000, "net of EMTL");
+    assert_eq!(limit_hit(ledger.deposit(a, ngn(40_000), None)), LimitKind::MaxBalance);
+
+    // ₦50,000 of debits per rolling day
+    let withdrawal = ledger.withdraw(a, ngn(3_000_000), None).unwrap();
+    ledger.transfer(a, b, ngn(1_500_000), None).unwrap();
+    assert_eq!(limit_hit(ledger.transfer(a, b, ngn(500_001), None)), LimitKind::DailyDebit);
+    assert_eq!(limit_hit(ledger.reverse_transaction(deposits[0], "Bounced")), LimitKind::DailyDebit);
+    assert_eq!(
+        ledger.debits_since(a, chrono::Utc::now() - chrono::Duration::hours(24)).unwrap(),
+        ngn(4_500_000)
+    );
+    ledger.reverse_transaction(withdrawal, "Teller error").unwrap();
+    ledger.transfer(a, b, ngn(500_001), None).unwrap();
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn tier_limits_apply_to_opening_balances_holds_and_fx_transfers() {
+    let mut ledger = Ledger::new();
+    let open = |ledger: &mut Ledger, owner: &str, initial: Money| {
+        ledger.create_account(owner.into(), initial, "First Bank".into(), "011".into()).map(u64::from)
+    };
+    assert_eq!(limit_hit(open(&mut ledger, "Ada", ngn(30_000_001))), LimitKind::MaxBalance);
+    assert!(ledger.accounts.values().all(|acc| acc.owner != "Ada"), "the account is not created");
+    let a = open(&mut ledger, "Ada", ngn(30_000_000)).unwrap() as u32;
+
+    // Authorization checks the hold; capture checks the day's debits again
+    assert_eq!(limit_hit(ledger.authorize(a, ngn(5_000_001), None, None)), LimitKind::SingleTransaction);
+    let hold = ledger.authorize(a, ngn(3_000_000), None, None).unwrap();
+    ledger.withdraw(a, ngn(2_500_000), None).unwrap();
+    assert_eq!(limit_hit(ledger.capture(hold, None, None)), LimitKind::DailyDebit);
+    ledger.capture(hold, Some(ngn(2_500_000)), None).unwrap();
+
+    // Each FX leg is checked in its own currency
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 0)).unwrap();
+    let dollars = open(&mut ledger, "Fola", usd(100_000)).unwrap() as u32;
+    let naira = open(&mut ledger, "Gbenga", ngn(29_000_000)).unwrap() as u32;
+    assert_eq!(limit_hit(ledger.fx_transfer(dollars, naira, usd(3_000), None)), LimitKind::MaxBalance);
+    assert_eq!(limit_hit(ledger.fx_transfer(a, dollars, ngn(5_000_001), None)), LimitKind::SingleTransaction);
+    assert_eq!(limit_hit(ledger.fx_transfer(a, dollars, ngn(1_500_000), None)), LimitKind::DailyDebit);
+    ledger.fx_transfer(dollars, naira, usd(600), None).unwrap();
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn accounts_from_before_tiers_keep_an_uncapped_balance_under_tier_three_limits() {
+    let (mut ledger, a) = ledger_with_account(0);
+    let mut snapshot = serde_json::to_value(&ledger).unwrap();
+    snapshot["accounts"][a.to_string()].as_object_mut().unwrap().remove("kyc_tier");
+    ledger = serde_json::from_value(snapshot).unwrap();
+    ledger.after_load().unwrap();
+    assert_eq!(ledger.accounts[&a].kyc_tier, KycTier::Tier3);
+
+    assert_eq!(limit_hit(ledger.deposit(a, ngn(500_000_001), None)), LimitKind::SingleTransaction);
+    for _ in 0..6 {
+        ledger.deposit(a, ngn(500_000_000), None).unwrap();
+    }
+    assert!(balance(&ledger, a) > 2_500_000_000, "no balance cap");
+    for _ in 0..5 {
+        ledger.withdraw(a, ngn(500_000_000), None).unwrap();
+    }
+    assert_eq!(limit_hit(ledger.withdraw(a, ngn(1), None)), LimitKind::DailyDebit);
+}
+
+#[test]
+fn upgrading_the_tier_raises_limits_and_records_the_operator() {
+    let (mut ledger, a) = ledger_with_account(4_000_000);
+    assert!(matches!(ledger.deposit(a, ngn(8_000_000), None), Err(LedgerError::LimitExceeded { .. })));
+
+    assert!(ledger.upgrade_kyc_tier(a, KycTier::Tier2, " ").is_err(), "operator required");
+    ledger.upgrade_kyc_tier(a, KycTier::Tier2, "ops.adaeze").unwrap();
+    ledger.deposit(a, ngn(8_000_000), None).unwrap();
+    assert!(ledger.upgrade_kyc_tier(a, KycTier::Tier1, "ops.adaeze").is_err(), "no downgrades");
+    ledger.upgrade_kyc_tier(a, KycTier::Tier3, "ops.tunde").unwrap();
+
+    let history: Vec<_> = ledger.accounts[&a].kyc_history.iter().map(|c| (c.from, c.to, c.by.as_str())).collect();
+    assert_eq!(history, vec![
+        (KycTier::Tier1, KycTier::Tier2, "ops.adaeze"),
+        (KycTier::Tier2, KycTier::Tier3, "ops.tunde"),
+    ]);
+
+    // Dollar accounts and accounts from older snapshots are not tier-limited
+    let dom = ledger.create_account("Alice".into(), usd(0), "First Bank".into(), "011".into()).unwrap();
+    ledger.deposit(dom, usd(10_000_000), None).unwrap();
+    let mut snapshot = serde_json::to_value(&ledger).unwrap();
+    snapshot["accounts"][a.to_string()].as_object_mut().unwrap().remove("kyc_tier");
+    let ledger: Ledger = serde_json::from_value(snapshot).unwrap();
+    assert_eq!(ledger.accounts[&a].kyc_tier, KycTier::Tier3);
+}
+
+fn customer_details(name: &str, bvn: Option<&str>, nin: Option<&str>) -> CustomerDetails {
+    CustomerDetails {
+        name: name.into(),
+        bvn: bvn.map(Into::into),
+        nin: nin.map(Into::into),
+        phone: "+2348031234567".into(),
+        email: Some("customer@example.com".into()),
+        address: None,
+    }
+}
+
+#[test]
+fn customers_have_unique_identifiers() {
+    let mut ledger = Ledger::new();
+    let ada = ledger.create_customer(customer_details(" Ada Obi ", Some("22212345678"), None)).unwrap();
+    assert_eq!(ledger.customer(ada).unwrap().name, "Ada Obi");
+
+    let duplicate = ledger.create_customer(customer_details("Ada O.", Some("22212345678"), Some("12345678901")));
+    assert_eq!(duplicate.unwrap_err().code(), "validation_error");
+    assert!(ledger.create_customer(customer_details("No Id", None, Some(""))).is_err(), "BVN or NIN required");
+    assert!(ledger.create_customer(customer_details("Short", Some("2221234"), None)).is_err());
+    ledger.create_customer(customer_details("Musa Bello", None, Some("12345678901"))).unwrap();
+    assert!(ledger.create_customer(customer_details("Musa B.", Some("22299999999"), Some("12345678901"))).is_err());
+    assert_eq!(ledger.customers.len(), 2);
+}
+
+#[test]
+fn accounts_open_with_all_their_options_or_not_at_all() {
+    let mut ledger = Ledger::new();
+    let customer = ledger.create_customer(customer_details("Ada Obi", Some("22212345678"), None)).unwrap();
+    let assets = ledger.create_group_account("Assets".into(), AccountType::Asset, Currency::NGN, None).unwrap();
+    let deposits = ledger.create_group_account("Deposits".into(), AccountType::Liability, Currency::NGN, None).unwrap();
+    let (accounts, transactions, next_id) = (ledger.accounts.len(), ledger.transactions.len(), ledger.next_account_id);
+    let open = |ledger: &mut Ledger, options: OpeningOptions| {
+        ledger.open_account("Ada".into(), ngn(5_000), "First Bank".into(), "011".into(), options)
+    };
+
+    for options in [
+        OpeningOptions { customer_id: Some(99), ..Default::default() },
+        OpeningOptions { parent_id: Some(assets), customer_id: Some(customer), ..Default::default() },
+        OpeningOptions { parent_id: Some(deposits), customer_id: Some(99), pending: true },
+    ] {
+        assert!(open(&mut ledger, options).is_err());
+        assert_eq!(ledger.accounts.len(), accounts + 1, "only the opening-balance account is added");
+        assert_eq!((ledger.transactions.len(), ledger.next_account_id), (transactions, next_id + 1));
+    }
+
+    let a = open(&mut ledger, OpeningOptions { parent_id: Some(deposits), pending: true, customer_id: Some(customer) }).unwrap();
+    let acc = &ledger.accounts[&a];
+    assert_eq!((acc.parent_id, acc.status, acc.customer_id), (Some(deposits), AccountStatus::Pending, Some(customer)));
+    assert_eq!(balance(&ledger, a), 5_000);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn customer_accounts_are_listed_with_balances_per_currency() {
+    let mut ledger = Ledger::new();
+    let ada = ledger.create_customer(customer_details("Ada Obi", Some("22212345678"), None)).unwrap();
+    let other = ledger.create_customer(customer_details("Musa Bello", Some("22287654321"), None)).unwrap();
+    let open = |ledger: &mut Ledger, amount: Money| {
+        ledger.create_account("Ada Obi".into(), amount, "First Bank".into(), "011".into()).unwrap()
+    };
+    let savings = open(&mut ledger, ngn(150_000));
+    let current = open(&mut ledger, ngn(25_000));
+    let dom = open(&mut ledger, usd(4_000));
+    let unlinked = open(&mut ledger, ngn(1_000));
+    for id in [savings, current, dom] {
+        ledger.attach_account(ada, id).unwrap();
+    }
+    ledger.attach_account(ada, savings).unwrap();
+    assert!(ledger.attach_account(other, savings).is_err(), "already belongs to Ada");
+    assert!(ledger.attach_account(ada, 0).is_err(), "settlement is internal");
+
+    let ids: Vec<u32> = ledger.customer_accounts(ada).unwrap().iter().map(|a| a.id).collect();
+    assert_eq!(ids, vec![savings, current, dom]);
+    assert_eq!(ledger.customer_balances(ada).unwrap(), vec![ngn(175_000), usd(4_000)]);
+    assert!(ledger.customer_accounts(other).unwrap().is_empty());
+    assert!(ledger.customer_accounts(99).is_err());
+
+    let by_owner: Vec<u32> = ledger.find_accounts_by_owner("Ada Obi").iter().map(|a| a.id).collect();
+    assert_eq!(by_owner, vec![savings, current, dom, unlinked]);
+}
+
+/// Joint account of 1,000 held by Ada with Musa and Ngozi as co-signatories,
+/// and a second account to pay into.
+fn joint_account(rule: MandateRule) -> (Ledger, u32, u32, [u64; 3]) {
+    let (mut ledger, a, b) = two_accounts();
+    let signatories = [
+        ledger.create_customer(customer_details("Ada Obi", Some("22212345678"), None)).unwrap(),
+        ledger.create_customer(customer_details("Musa Bello", Some("22287654321"), None)).unwrap(),
+        ledger.create_customer(customer_details("Ngozi Eze", None, Some("12345678901"))).unwrap(),
+    ];
+    ledger.attach_account(signatories[0], a).unwrap();
+    ledger.set_mandate(a, signatories.to_vec(), rule).unwrap();
+    (ledger, a, b, signatories)
+}
+
+fn pending(submission: Submission) -> u64 {
+    match submission {
+        Submission::Pending { instruction_id } => instruction_id,
+        other => panic!("expected a pending instruction, got {:?}", other),
+    }
+}
+
+#[test]
+fn joint_account_debits_post_once_the_mandate_is_met() {
+    let (mut ledger, a, b, [ada, musa, ngozi]) = joint_account(MandateRule::NOfM { required: 2 });
+    assert!(ledger.transfer(a, b, ngn(100), None).is_err(), "direct debits bypass the mandate");
+    assert!(ledger.withdraw(a, ngn(100), None).is_err());
+    assert_eq!(ledger.customer_accounts(ngozi).unwrap().len(), 1, "co-signatories see the account");
+
+    let transfer = Operation::Transfer { from: a, to: b, amount: ngn(300) };
+    let id = pending(ledger.submit(transfer, Some("Rent".into()), Some(ada), None).unwrap());
+    assert_eq!(balance(&ledger, a), 1_000);
+    assert!(ledger.approve_instruction(id, ada).is_err(), "already approved");
+    assert!(ledger.approve_instruction(id, 99).is_err(), "not a signatory");
+    assert!(ledger.set_mandate(a, vec![ada, musa], MandateRule::All).is_err(), "instruction pending");
+
+    let instruction = ledger.approve_instruction(id, musa).unwrap().clone();
+    assert_eq!(instruction.status, InstructionStatus::Posted);
+    let tx = ledger.transaction(instruction.tx_id.unwrap()).unwrap();
+    let approvers: Vec<_> = tx.approvals.iter().map(|a| a.approver.clone()).collect();
+    assert_eq!(approvers, vec![Approver::Signatory(ada), Approver::Signatory(musa)]);
+    assert_eq!((balance(&ledger, a), balance(&ledger, b)), (700, 300));
+
+    // A failed posting leaves the instruction pending without the last approval
+    let withdrawal = Operation::Withdrawal { account_id: a, amount: ngn(900) };
+    let id = pending(ledger.submit(withdrawal, None, None, None).unwrap());
+    ledger.approve_instruction(id, ngozi).unwrap();
+    assert!(matches!(ledger.approve_instruction(id, ada), Err(LedgerError::InsufficientFunds { .. })));
+    let instruction = ledger.instruction(id).unwrap();
+    assert_eq!((instruction.status, instruction.approvals.len()), (InstructionStatus::Pending, 1));
+    ledger.reject_instruction(id, musa, "Not agreed").unwrap();
+    assert!(ledger.approve_instruction(id, ada).is_err(), "rejected");
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn joint_account_mandate_rules_and_expiry() {
+    let (mut ledger, a, b, [ada, musa, ngozi]) = joint_account(MandateRule::AnyOne);
+    let transfer = Operation::Transfer { from: a, to: b, amount: ngn(100) };
+    assert!(matches!(ledger.submit(transfer.clone(), None, Some(musa), None).unwrap(), Submission::Posted { .. }));
+    assert!(ledger.submit(transfer.clone(), None, Some(42), None).is_err(), "not a signatory");
+
+    ledger.clear_mandate(a).unwrap();
+    ledger.set_mandate(a, vec![ada, musa, ngozi], MandateRule::All).unwrap();
+    let id = pending(ledger.submit(transfer.clone(), None, Some(ada), None).unwrap());
+    ledger.approve_instruction(id, musa).unwrap();
+    let later = chrono::Utc::now() + chrono::Duration::days(4);
+    assert_eq!(ledger.expire_instructions(later), vec![id]);
+    assert_eq!(ledger.instruction(id).unwrap().status, InstructionStatus::Expired);
+    assert_eq!(balance(&ledger, a), 900);
+
+    // An out-of-range lifetime is refused by validation, and by submission if it got in anyway
+    let settings = LedgerSettings { instruction_ttl_secs: i64::MAX, ..ledger.settings.clone() };
+    assert!(matches!(settings.validate(), Err(LedgerError::Validation(_))));
+    ledger.settings = settings;
+    let err = ledger.submit(transfer.clone(), None, Some(ada), None).unwrap_err();
+    assert_eq!(err.code(), "validation_error");
+
+    assert!(ledger.set_mandate(a, vec![ada], MandateRule::AnyOne).is_err(), "joint accounts need two signatories");
+    assert!(ledger.set_mandate(a, vec![ada, musa], MandateRule::NOfM { required: 3 }).is_err());
+    assert!(ledger.set_mandate(a, vec![musa, ngozi], MandateRule::All).is_err(), "Ada holds the account");
+}
+
+#[test]
+fn joint_accounts_cannot_be_debited_by_fx_transfers_holds_or_standing_orders() {
+    let (mut ledger, a, b, [ada, musa, _]) = joint_account(MandateRule::AnyOne);
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 0)).unwrap();
+    let dollars = ledger.create_account("Fola".into(), usd(0), "First Bank".into(), "011".into()).unwrap();
+    let start = chrono::Utc::now();
+    let order = |ledger: &mut Ledger, from: u32, to: u32| {
+        ledger.create_standing_order(from, to, ngn(100), None, Frequency::Monthly, start, None, FailurePolicy::Skip)
+    };
+
+    let mandated = |result: Result<u64, LedgerError>| matches!(result, Err(e) if e.to_string().contains("mandate"));
+    assert!(mandated(ledger.fx_transfer(a, dollars, ngn(1_500), None)));
+    assert!(mandated(ledger.authorize(a, ngn(100), None, None)));
+    assert!(mandated(order(&mut ledger, a, b)));
+    let paying = order(&mut ledger, b, a).unwrap();
+    assert_eq!(balance(&ledger, a), 1_000);
+
+    // Nor can a mandate be added while a standing order pays out of the account
+    assert!(ledger.set_mandate(b, vec![ada, musa], MandateRule::All).is_err());
+    ledger.cancel_standing_order(paying).unwrap();
+    ledger.set_mandate(b, vec![ada, musa], MandateRule::All).unwrap();
+}
+
+#[test]
+fn approval_thresholds_change_only_once_a_second_operator_approves() {
+    let (mut ledger, a, b) = two_accounts();
+    let mut settings = LedgerSettings { hold_ttl_secs: 60, ..ledger.settings.clone() };
+    settings.approval_thresholds.insert(Currency::NGN, 500);
+
+    assert!(matches!(ledger.update_settings(settings.clone(), " "), Err(LedgerError::Validation(_))));
+    let approval_id = ledger.update_settings(settings.clone(), "ops-1").unwrap().expect("thresholds are queued");
+    assert_eq!(ledger.settings.hold_ttl_secs, 60);
+    assert_eq!(ledger.settings.approval_thresholds[&Currency::NGN], 100_000_000);
+    ledger.transfer(a, b, ngn(600), None).unwrap();
+    let thresholds = Operation::ApprovalThresholds { thresholds: settings.approval_thresholds.clone() };
+    assert!(matches!(ledger.submit(thresholds, None, None, Some("ops-1")), Err(LedgerError::Validation(_))));
+
+    assert!(ledger.approve_request(approval_id, "ops-1").is_err());
+    ledger.approve_request(approval_id, "ops-2").unwrap();
+    assert_eq!(ledger.settings, settings);
+    awaiting(ledger.submit(Operation::Transfer { from: a, to: b, amount: ngn(501) }, None, None, Some("ops-1")).unwrap());
+    let made_by: Vec<_> = ledger
+        .settings_changes
+        .iter()
+        .map(|c| (c.made_by.as_str(), c.approved_by.as_deref()))
+        .collect();
+    assert_eq!(made_by, [("ops-1", None), ("ops-1", Some("ops-2"))]);
+    assert_eq!(ledger.update_settings(settings, "ops-3").unwrap(), None);
+    assert_eq!(ledger.settings_changes.len(), 2);
+}
+
+fn awaiting(submission: Submission) -> u64 {
+    match submission {
+        Submission::AwaitingApproval { approval_id } => approval_id,
+        other => panic!("expected an approval request, got {:?}", other),
+    }
+}
+
+#[test]
+fn operations_above_the_threshold_wait_for_a_second_operator() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 500);
+    let small = Operation::Transfer { from: a, to: b, amount: ngn(500) };
+    assert!(matches!(ledger.submit(small, None, None, None).unwrap(), Submission::Posted { .. }));
+
+    let large = Operation::Transfer { from: a, to: b, amount: ngn(600) };
+    assert!(ledger.submit(large.clone(), None, None, None).is_err(), "a maker is required");
+    let id = awaiting(ledger.submit(large, Some("Supplier".into()), None, Some("ops-1")).unwrap());
+    assert_eq!((balance(&ledger, a), balance(&ledger, b)), (500, 500));
+    assert!(ledger.approve_request(id, "ops-1").is_err(), "makers cannot check their own requests");
+
+    // Approval re-validates the balance; the request waits until it passes
+    assert!(matches!(ledger.approve_request(id, "ops-2"), Err(LedgerError::InsufficientFunds { .. })));
+    assert_eq!(ledger.approval_request(id).unwrap().status, ApprovalStatus::Pending);
+    ledger.deposit(a, ngn(100), None).unwrap();
+    let request = ledger.approve_request(id, "ops-2").unwrap().clone();
+    assert_eq!((request.status, request.maker.as_str(), request.checker.as_deref()), (ApprovalStatus::Approved, "ops-1", Some("ops-2")));
+    let tx = ledger.transaction(request.tx_id.unwrap()).unwrap();
+    assert_eq!(tx.approvals[0].approver, Approver::Operator("ops-2".into()));
+    assert_eq!((balance(&ledger, a), balance(&ledger, b)), (0, 1_100));
+    assert!(ledger.reject_request(id, "ops-3", "Too late").is_err(), "already approved");
+
+    let withdrawal = Operation::Withdrawal { account_id: b, amount: ngn(1_000) };
+    let id = awaiting(ledger.submit(withdrawal, None, None, Some("ops-1")).unwrap());
+    assert!(ledger.reject_request(id, "ops-2", " ").is_err(), "a reason is required");
+    let request = ledger.reject_request(id, "ops-2", "Unverified beneficiary").unwrap();
+    assert_eq!((request.status, request.rejection_reason.as_deref()), (ApprovalStatus::Rejected, Some("Unverified beneficiary")));
+    assert!(ledger.approve_request(id, "ops-3").is_err(), "rejected");
+    assert_eq!(balance(&ledger, b), 1_100);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn reversals_need_a_checker_above_the_threshold_and_signatories_on_joint_accounts() {
+    let (mut ledger, a, b, [ada, musa, _]) = joint_account(MandateRule::NOfM { required: 2 });
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 500);
+    let deposit = ledger.deposit(b, ngn(600), None).unwrap();
+    let reversal = Operation::Reversal { tx_id: deposit, reason: "Bounced".into() };
+    assert!(ledger.submit(reversal.clone(), None, None, None).is_err(), "a maker is required");
+    let id = awaiting(ledger.submit(reversal, None, None, Some("ops-1")).unwrap());
+    assert_eq!(balance(&ledger, b), 600);
+    let request = ledger.approve_request(id, "ops-2").unwrap().clone();
+    assert_eq!(ledger.transaction(deposit).unwrap().reversed_by, request.tx_id);
+    assert_eq!(balance(&ledger, b), 0);
+
+    // Reversing a credit to a joint account debits it, which needs its signatories
+    let deposit = ledger.deposit(a, ngn(200), None).unwrap();
+    assert!(ledger.reverse_transaction(deposit, "Duplicate").is_err(), "direct reversals bypass the mandate");
+    let reversal = Operation::Reversal { tx_id: deposit, reason: "Duplicate".into() };
+    let id = pending(ledger.submit(reversal, None, Some(ada), None).unwrap());
+    assert_eq!(balance(&ledger, a), 1_200);
+    let instruction = ledger.approve_instruction(id, musa).unwrap().clone();
+    assert_eq!(ledger.transaction(instruction.tx_id.unwrap()).unwrap().reverses, Some(deposit));
+    assert_eq!(balance(&ledger, a), 1_000);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn approved_journals_post_and_joint_debits_move_on_to_signatories() {
+    let (mut ledger, a, b, [ada, musa, ngozi]) = joint_account(MandateRule::All);
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 500);
+    let journal = Operation::Journal { entries: vec![entry(a, Side::Debit, 700), entry(b, Side::Credit, 700)] };
+    assert!(ledger.submit(journal.clone(), None, Some(ada), Some("ops-1")).is_err(), "journals have no signatory");
+    let unbalanced = Operation::Journal { entries: vec![entry(a, Side::Debit, 700), entry(b, Side::Credit, 600)] };
+    assert!(ledger.submit(unbalanced, None, None, Some("ops-1")).is_err(), "rejected before it is queued");
+
+    let id = awaiting(ledger.submit(journal, Some("Correction".into()), None, Some("ops-1")).unwrap());
+    let request = ledger.approve_request(id, "ops-2").unwrap().clone();
+    assert_eq!(ledger.transaction(request.tx_id.unwrap()).unwrap().description.as_deref(), Some("Correction"));
+    assert_eq!((balance(&ledger, a), balance(&ledger, b)), (300, 700));
+
+    let transfer = Operation::Transfer { from: b, to: a, amount: ngn(600) };
+    let id = awaiting(ledger.submit(transfer, None, None, Some("ops-1")).unwrap());
+    ledger.approve_request(id, "ops-2").unwrap();
+    let withdrawal = Operation::Withdrawal { account_id: a, amount: ngn(800) };
+    let id = awaiting(ledger.submit(withdrawal, None, Some(ada), Some("ops-1")).unwrap());
+    let instruction_id = ledger.approve_request(id, "ops-2").unwrap().instruction_id.unwrap();
+    assert_eq!(balance(&ledger, a), 900, "still needs the signatories");
+    ledger.approve_instruction(instruction_id, musa).unwrap();
+    let instruction = ledger.approve_instruction(instruction_id, ngozi).unwrap().clone();
+    let tx = ledger.transaction(instruction.tx_id.unwrap()).unwrap();
+    let approvers: Vec<_> = tx.approvals.iter().map(|a| a.approver.clone()).collect();
+    assert_eq!(approvers, vec![Approver::Operator("ops-2".into()), Approver::Signatory(ada), Approver::Signatory(musa), Approver::Signatory(ngozi)]);
+    assert_eq!(balance(&ledger, a), 100);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn fx_transfers_are_held_for_approval_on_the_amount_debited() {
+    let mut ledger = Ledger::new();
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 150_000);
+    ledger.fx_rates.upsert(usd_ngn_rate(1_500.0, 0)).unwrap();
+    let naira = ledger.create_account("Hauwa".into(), ngn(300_000), "First Bank".into(), "011".into()).unwrap();
+    let dollars = ledger.create_account("Ike".into(), usd(1_000), "First Bank".into(), "011".into()).unwrap();
+
+    // $10 credits ₦15,000, above the naira threshold, but only the debited side counts
+    let inbound = Operation::FxTransfer { from: dollars, to: naira, amount: usd(1_000) };
+    assert!(matches!(ledger.submit(inbound, None, None, None).unwrap(), Submission::Posted { .. }));
+
+    let outbound = Operation::FxTransfer { from: naira, to: dollars, amount: ngn(300_000) };
+    let id = awaiting(ledger.submit(outbound, None, None, Some("ops-1")).unwrap());
+    assert_eq!(ledger.approval_request(id).unwrap().status, ApprovalStatus::Pending);
+    assert_eq!(balance(&ledger, dollars), 0);
+    let tx_id = ledger.approve_request(id, "ops-2").unwrap().tx_id.unwrap();
+    assert!(ledger.transaction(tx_id).unwrap().fx.is_some());
+    assert_eq!(balance(&ledger, dollars), 200);
+    assert_books_balance(&ledger);
+}
+
+#[test]
+fn holds_standing_orders_captures_and_lien_enforcements_above_the_threshold_wait_for_a_checker() {
+    let (mut ledger, a, b) = two_accounts();
+    ledger.deposit(a, ngn(2_000), None).unwrap();
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 500);
+    let approvers = |ledger: &Ledger, tx_id: u64| -> Vec<Approver> {
+        ledger.transaction(tx_id).unwrap().approvals.iter().map(|a| a.approver.clone()).collect()
+    };
+    let checker = vec![Approver::Operator("ops-2".into())];
+
+    // A hold is placed once a checker approves it, and its capture needs no second approval
+    let hold = Operation::Hold { account_id: a, amount: ngn(600), ttl_secs: None };
+    assert!(ledger.submit(hold.clone(), None, None, None).is_err(), "a maker is required");
+    let id = awaiting(ledger.submit(hold, None, None, Some("ops-1")).unwrap());
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(3_000));
+    let hold_id = ledger.approve_request(id, "ops-2").unwrap().hold_id.unwrap();
+    assert_eq!(ledger.available_balance(a).unwrap(), ngn(2_400));
+    let capture = Operation::Capture { hold_id, amount: None };
+    let Submission::Posted { tx_id } = ledger.submit(capture, None, None, None).unwrap() else {
+        panic!("an approved hold is captured without a checker");
+    };
+    assert_eq!(approvers(&ledger, tx_id), checker);
+
+    // Likewise a standing order, whose payments carry the approval
+    let start = chrono::Utc::now();
+    let order = Operation::StandingOrder {
+        from: a,
+        to: b,
+        amount: ngn(600),
+        frequency: Frequency::Daily,
+        start,
+        end: None,
+        policy: FailurePolicy::Skip,
+    };
+    let id = awaiting(ledger.submit(order, None, None, Some("ops-1")).unwrap());
+    let order_id = ledger.approve_request(id, "ops-2").unwrap().standing_order_id.unwrap();
+    let runs = ledger.run_due_standing_orders(start);
+    assert_eq!(runs[0].outcome, ExecutionOutcome::Posted);
+    assert_eq!(approvers(&ledger, runs[0].tx_id.unwrap()), checker);
+    ledger.cancel_standing_order(order_id).unwrap();
+    assert_eq!(balance(&ledger, a), 1_800);
+
+    // Payments of an order set up below the threshold are checked against it every time
+    let small = ledger.create_standing_order(a, b, ngn(500), None, Frequency::Daily, start, None, FailurePolicy::Skip).unwrap();
+    let unapproved = ledger.authorize(a, ngn(500), None, None).unwrap();
+    ledger.settings.approval_thresholds.insert(Currency::NGN, 400);
+    let runs = ledger.run_due_standing_orders(start);
+    assert_eq!((runs[0].outcome, runs[0].tx_id), (ExecutionOutcome::AwaitingApproval, None));
+    let request = ledger.approval_request(runs[0].approval_id.unwrap()).unwrap();
+    assert_eq!(request.maker, format!("standing order {}", small));
+    assert_eq!(ledger.standing_order(small).unwrap().occurrence, 1, "the order moves on");
+    assert_eq!(balance(&ledger, a), 1_800);
+
+    // Captures of holds no checker approved queue too, as do lien enforcements
+    let capture = Operation::Capture { hold_id: unapproved, amount: None };
+    let id = awaiting(ledger.submit(capture, None, None, Some("ops-1")).unwrap());
+    ledger.approve_request(id, "ops-2").unwrap();
+    let lien = ledger.place_lien(a, ngn(450), "Tax recovery".into(), "FIRS/2025/91".into(), None).unwrap();
+    let id = awaiting(ledger.submit(Operation::LienEnforcement { account_id: a, lien_id: lien }, None, None, Some("ops-1")).unwrap());
+    assert_eq!(ledger.lien(a, lien).unwrap().status, LienStatus::Active);
+    let tx_id = ledger.approve_request(id, "ops-2").unwrap().tx_id.unwrap();
+    assert_eq!(ledger.lien(a, lien).unwrap().enforce_tx_id, Some(tx_id));
+    assert_eq!(balance(&ledger, a), 850);
+    assert_books_balance(&ledger);
+}